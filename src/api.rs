@@ -1,14 +1,21 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use async_std::io::ReadExt;
+use async_std::stream::Stream;
 use chrono::{DateTime, Utc};
-use sqlx::SqlitePool;
+use sqlx::sqlite::SqliteConnection;
+use sqlx::{Acquire, Sqlite, SqlitePool};
 use thiserror::Error;
 
 use crate::db::*;
+use crate::lsp::{DocumentSymbol, Range};
+use crate::project::ProjectSettings;
 use crate::types::*;
 
 #[derive(Debug, Error)]
@@ -29,6 +36,34 @@ pub enum SourcetrailError {
     ParentNotFound(i64),
     #[error("file with id {0} does not exist in the database")]
     FileNotFound(i64),
+    #[error("node with id {0} does not exist in the database")]
+    NodeNotFound(i64),
+    #[error("local symbol with id {0} does not exist in the database")]
+    LocalSymbolNotFound(i64),
+    #[error("node with id {id} has type {found:?}, expected {expected:?}")]
+    UnexpectedNodeType {
+        id: i64,
+        expected: NodeType,
+        found: NodeType,
+    },
+    #[error("node with id {id} has type {parent:?}, which cannot contain a {child:?}")]
+    InvalidParent {
+        id: i64,
+        parent: NodeType,
+        child: NodeType,
+    },
+    #[error("unsupported storage version {found}, expected {expected}")]
+    UnsupportedStorageVersion { found: i64, expected: i64 },
+    #[error("a transaction is already active")]
+    TransactionActive,
+    #[error("no transaction is active")]
+    NoTransaction,
+    #[error("unknown kind: {0}")]
+    UnknownKind(String),
+    #[error("invalid qualified name: {0}")]
+    InvalidQualifiedName(String),
+    #[error("unexpected database schema: {0}")]
+    SchemaMismatch(String),
     #[error("name hierarchy must contain at least one element")]
     EmptyNameHierarchy,
     #[error("invalid source range")]
@@ -47,6 +82,63 @@ pub enum SourcetrailError {
     TypeConversion(anyhow::Error),
 }
 
+impl PartialEq for SourcetrailError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Deserialize, Self::Deserialize)
+            | (Self::Serialize, Self::Serialize)
+            | (Self::NoDatabaseOpen, Self::NoDatabaseOpen)
+            | (Self::EmptyNameHierarchy, Self::EmptyNameHierarchy)
+            | (Self::InvalidSourceRange, Self::InvalidSourceRange)
+            | (Self::TransactionActive, Self::TransactionActive)
+            | (Self::NoTransaction, Self::NoTransaction) => true,
+            (Self::File(a), Self::File(b))
+            | (Self::UnknownKind(a), Self::UnknownKind(b))
+            | (Self::InvalidQualifiedName(a), Self::InvalidQualifiedName(b))
+            | (Self::SchemaMismatch(a), Self::SchemaMismatch(b)) => a == b,
+            (Self::ParentNotFound(a), Self::ParentNotFound(b))
+            | (Self::FileNotFound(a), Self::FileNotFound(b))
+            | (Self::NodeNotFound(a), Self::NodeNotFound(b))
+            | (Self::LocalSymbolNotFound(a), Self::LocalSymbolNotFound(b)) => a == b,
+            (
+                Self::UnexpectedNodeType {
+                    id: a,
+                    expected: ea,
+                    found: fa,
+                },
+                Self::UnexpectedNodeType {
+                    id: b,
+                    expected: eb,
+                    found: fb,
+                },
+            ) => a == b && ea == eb && fa == fb,
+            (
+                Self::InvalidParent {
+                    id: a,
+                    parent: pa,
+                    child: ca,
+                },
+                Self::InvalidParent {
+                    id: b,
+                    parent: pb,
+                    child: cb,
+                },
+            ) => a == b && pa == pb && ca == cb,
+            (
+                Self::UnsupportedStorageVersion {
+                    found: fa,
+                    expected: ea,
+                },
+                Self::UnsupportedStorageVersion {
+                    found: fb,
+                    expected: eb,
+                },
+            ) => fa == fb && ea == eb,
+            _ => false,
+        }
+    }
+}
+
 impl SourcetrailError {
     pub fn convert<E>(e: E) -> Self
     where
@@ -103,10 +195,12 @@ pub struct NodeRecorder<'a, 'b> {
     name: Cow<'b, str>,
     prefix: Cow<'b, str>,
     postfix: Cow<'b, str>,
-    delimiter: Cow<'b, str>,
+    delimiter: Option<Cow<'b, str>>,
     parent_id: Option<i64>,
     is_indexed: bool,
     node_type: NodeType,
+    access: Option<ComponentAccessType>,
+    strict_parent: bool,
 }
 
 impl<'a, 'b> NodeRecorder<'a, 'b> {
@@ -116,10 +210,12 @@ impl<'a, 'b> NodeRecorder<'a, 'b> {
             name: Cow::Borrowed(""),
             prefix: Cow::Borrowed(""),
             postfix: Cow::Borrowed(""),
-            delimiter: Cow::Borrowed(NameHierarchy::NAME_DELIMITER_CXX),
+            delimiter: None,
             parent_id: None,
             is_indexed: true,
             node_type: kind,
+            access: None,
+            strict_parent: false,
         }
     }
 
@@ -151,7 +247,7 @@ impl<'a, 'b> NodeRecorder<'a, 'b> {
     }
 
     pub fn set_delimiter(&mut self, delimiter: impl Into<Cow<'b, str>>) {
-        self.delimiter = delimiter.into();
+        self.delimiter = Some(delimiter.into());
     }
 
     pub fn delimiter(mut self, delimiter: impl Into<Cow<'b, str>>) -> Self {
@@ -177,18 +273,167 @@ impl<'a, 'b> NodeRecorder<'a, 'b> {
         self
     }
 
+    /// Records the node as referenced but not defined by the indexed code,
+    /// e.g. a class from an external library. Shorthand for `indexed(false)`.
+    pub fn implicit(self) -> Self {
+        self.indexed(false)
+    }
+
+    pub fn set_access(&mut self, access: impl Into<Option<ComponentAccessType>>) {
+        self.access = access.into();
+    }
+
+    pub fn access(mut self, access: impl Into<Option<ComponentAccessType>>) -> Self {
+        self.set_access(access);
+        self
+    }
+
+    /// Like [`access`](Self::access), but `ComponentAccessType::None` records
+    /// no access specifier at all.
+    pub fn access_type(self, access: ComponentAccessType) -> Self {
+        self.access((access != ComponentAccessType::None).then_some(access))
+    }
+
+    /// When set, committing fails with `InvalidParent` if the parent's type
+    /// cannot contain this node's type (see `NodeType::can_contain`).
+    pub fn set_strict_parent(&mut self, strict: bool) {
+        self.strict_parent = strict;
+    }
+
+    pub fn strict_parent(mut self, strict: bool) -> Self {
+        self.set_strict_parent(strict);
+        self
+    }
+
     pub async fn commit(self) -> Result<i64, SourcetrailError> {
-        self.db
+        let delimiter = self
+            .delimiter
+            .unwrap_or_else(|| Cow::Owned(self.db.default_delimiter.clone()));
+
+        let id = self
+            .db
             .full_record_node(
                 self.name,
                 self.prefix,
                 self.postfix,
-                self.delimiter,
+                delimiter,
                 self.parent_id,
                 self.is_indexed,
                 self.node_type,
+                self.strict_parent,
             )
-            .await
+            .await?;
+
+        if let Some(access) = self.access {
+            self.db.record_access_specifier(id, access).await?;
+        }
+
+        Ok(id)
+    }
+
+    /// Like [`commit`](Self::commit), but returns a [`NodeHandle`] from which
+    /// children can be recorded.
+    pub async fn commit_handle(self) -> Result<NodeHandle, SourcetrailError> {
+        Ok(NodeHandle::new(self.commit().await?))
+    }
+}
+
+/// A recorded node, used to start recording its children.
+///
+/// ```
+/// use sourcetrail::prelude::*;
+///
+/// # async_std::task::block_on(async {
+/// # let dir = tempfile::tempdir().unwrap();
+/// let mut db = SourcetrailDB::create(dir.path().join("shapes")).await?;
+///
+/// let shape = db.record_class().name("Shape").commit_handle().await?;
+/// let area = shape
+///     .child(&mut db, NodeType::NodeMethod)
+///     .name("area")
+///     .commit()
+///     .await?;
+///
+/// assert_eq!(db.get_qualified_name(area).await?, "Shape::area");
+/// # Ok::<(), SourcetrailError>(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NodeHandle {
+    id: i64,
+}
+
+impl NodeHandle {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn child<'a, 'b>(&self, db: &'a mut SourcetrailDB, kind: NodeType) -> NodeRecorder<'a, 'b> {
+        db.record_node(kind).parent(self.id)
+    }
+}
+
+impl From<NodeHandle> for i64 {
+    fn from(handle: NodeHandle) -> Self {
+        handle.id
+    }
+}
+
+pub struct ReferenceRecorder<'a, 'b> {
+    db: &'a mut SourcetrailDB,
+    source_id: i64,
+    target_id: i64,
+    kind: EdgeType,
+    label: Option<Cow<'b, str>>,
+}
+
+impl<'a, 'b> ReferenceRecorder<'a, 'b> {
+    pub fn new(db: &'a mut SourcetrailDB, source_id: i64, target_id: i64, kind: EdgeType) -> Self {
+        Self {
+            db,
+            source_id,
+            target_id,
+            kind,
+            label: None,
+        }
+    }
+
+    pub fn set_label(&mut self, label: impl Into<Cow<'b, str>>) {
+        self.label = Some(label.into());
+    }
+
+    pub fn label(mut self, label: impl Into<Cow<'b, str>>) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    pub async fn commit(self) -> Result<i64, SourcetrailError> {
+        let mut executor = self.db.executor().await;
+        let mut tx = executor.begin().await?;
+
+        let elem_id = ElementDAO::new(&mut *tx).await?;
+        EdgeDAO::new(
+            &mut *tx,
+            Edge::new(elem_id, self.kind, self.source_id, self.target_id),
+        )
+        .await?;
+
+        // labels are stored as element components of the edge's element
+        if let Some(label) = self.label {
+            ElementComponentDAO::new(
+                &mut *tx,
+                ElementComponent::new(0, elem_id, ElementComponentType::Label, label),
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(elem_id)
     }
 }
 
@@ -272,6 +517,20 @@ impl<'a> SourceLocationRecorder<'a> {
             return Err(SourcetrailError::source_location("missing end position"));
         }
 
+        if self.start_line < 0 || self.start_column < 0 || self.end_line < 0 || self.end_column < 0
+        {
+            return Err(SourcetrailError::source_location(
+                "position must be positive",
+            ));
+        }
+
+        // Sourcetrail lines are one-based
+        if self.start_line == 0 {
+            return Err(SourcetrailError::source_location(
+                "position must be positive",
+            ));
+        }
+
         if self.start_line > self.end_line
             || self.start_line == self.end_line && self.start_column >= self.end_column
         {
@@ -396,11 +655,14 @@ impl<'a> UnsolvedSymbolRecorder<'a> {
             [NameElementBuilder::new().name("unsolved symbol").build()],
         )?;
 
-        let unsolved_symbol_id = self.db.record_symbol(&hierarchy).await?;
+        let unsolved_symbol_id = self
+            .db
+            .record_symbol(&hierarchy, NodeType::NodeSymbol)
+            .await?;
 
-        let elem_id = ElementDAO::new(&self.db.database).await?;
+        let elem_id = ElementDAO::new(&mut self.db.executor().await).await?;
         let reference_id = EdgeDAO::new(
-            &self.db.database,
+            &mut self.db.executor().await,
             Edge::new(elem_id, reference_type, self.symbol_id, unsolved_symbol_id),
         )
         .await?;
@@ -493,7 +755,7 @@ impl<'a, 'b> ErrorRecorder<'a, 'b> {
         self
     }
 
-    pub async fn commit(self) -> Result<(), SourcetrailError> {
+    pub async fn commit(self) -> Result<i64, SourcetrailError> {
         if self.file_id == -1 {
             return Err(SourcetrailError::error_location("missing file"));
         }
@@ -516,9 +778,9 @@ impl<'a, 'b> ErrorRecorder<'a, 'b> {
             return Err(SourcetrailError::error_location("invalid source range"));
         }
 
-        let elem_id = ElementDAO::new(&self.db.database).await?;
+        let elem_id = ElementDAO::new(&mut self.db.executor().await).await?;
         ErrorDAO::new(
-            &self.db.database,
+            &mut self.db.executor().await,
             Error::new(elem_id, self.msg, self.fatal, true, ""),
         )
         .await?;
@@ -534,7 +796,7 @@ impl<'a, 'b> ErrorRecorder<'a, 'b> {
                 SourceLocationType::IndexerError,
             )
             .await?;
-        Ok(())
+        Ok(elem_id)
     }
 }
 
@@ -544,6 +806,7 @@ pub struct FileRecorder<'a, 'b> {
     modification_time: DateTime<Utc>,
     content: Cow<'b, str>,
     indexed: bool,
+    language: Cow<'b, str>,
 }
 
 impl<'a, 'b> FileRecorder<'a, 'b> {
@@ -554,6 +817,7 @@ impl<'a, 'b> FileRecorder<'a, 'b> {
             modification_time: chrono::offset::Utc::now(),
             content: Cow::Borrowed(""),
             indexed: true,
+            language: Cow::Borrowed(""),
         }
     }
 
@@ -584,6 +848,17 @@ impl<'a, 'b> FileRecorder<'a, 'b> {
         self
     }
 
+    /// Sets the content from raw bytes; invalid UTF-8 sequences are replaced
+    /// with U+FFFD, as Sourcetrail stores file content as text.
+    pub fn set_content_bytes(&mut self, bytes: &'b [u8]) {
+        self.content = String::from_utf8_lossy(bytes);
+    }
+
+    pub fn content_bytes(mut self, bytes: &'b [u8]) -> Self {
+        self.set_content_bytes(bytes);
+        self
+    }
+
     pub fn set_indexed(&mut self, indexed: bool) {
         self.indexed = indexed;
     }
@@ -593,6 +868,15 @@ impl<'a, 'b> FileRecorder<'a, 'b> {
         self
     }
 
+    pub fn set_language(&mut self, language: impl Into<Cow<'b, str>>) {
+        self.language = language.into();
+    }
+
+    pub fn language(mut self, language: impl Into<Cow<'b, str>>) -> Self {
+        self.set_language(language);
+        self
+    }
+
     pub async fn commit_file(self, path: impl AsRef<Path>) -> Result<i64, SourcetrailError> {
         let path = path.as_ref();
         let mut file = async_std::fs::File::open(path)
@@ -606,14 +890,21 @@ impl<'a, 'b> FileRecorder<'a, 'b> {
 
         let modified = meta.modified().map_err(SourcetrailError::file_recorder)?;
 
-        let mut buf = String::with_capacity(meta.len() as _);
-        file.read_to_string(&mut buf)
+        let mut buf = Vec::with_capacity(meta.len() as _);
+        file.read_to_end(&mut buf)
             .await
             .map_err(SourcetrailError::file_recorder)?;
 
         self.path(path)
             .modification_time(modified)
-            .content(buf)
+            .commit_bytes(&buf)
+            .await
+    }
+
+    /// Commits in-memory content that may not be valid UTF-8 (see
+    /// `set_content_bytes`).
+    pub async fn commit_bytes(self, bytes: &[u8]) -> Result<i64, SourcetrailError> {
+        self.content(String::from_utf8_lossy(bytes).into_owned())
             .commit()
             .await
     }
@@ -624,30 +915,369 @@ impl<'a, 'b> FileRecorder<'a, 'b> {
             .ok_or_else(|| SourcetrailError::file_recorder_with("missing file path"))?;
 
         self.db
-            .record_file_with(path, self.modification_time, self.content, self.indexed)
+            .record_file_with(
+                path,
+                self.modification_time,
+                self.content,
+                self.indexed,
+                self.language,
+            )
             .await
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    /// Write-ahead logging. Sourcetrail expects a plain database file, so
+    /// call [`SourcetrailDB::checkpoint`] or [`SourcetrailDB::close`] before
+    /// handing the database over to merge the `-wal` file back.
+    Wal,
+    Off,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Synchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+/// Row counts of a database, as returned by [`SourcetrailDB::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DbStats {
+    nodes: u64,
+    edges: u64,
+    files: u64,
+    symbols: u64,
+    nodes_by_type: HashMap<NodeType, u64>,
+}
+
+impl DbStats {
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn edges(&self) -> u64 {
+        self.edges
+    }
+
+    pub fn files(&self) -> u64 {
+        self.files
+    }
+
+    pub fn symbols(&self) -> u64 {
+        self.symbols
+    }
+
+    pub fn nodes_by_type(&self) -> &HashMap<NodeType, u64> {
+        &self.nodes_by_type
+    }
+
+    pub fn nodes_of_type(&self, node_type: NodeType) -> u64 {
+        self.nodes_by_type.get(&node_type).copied().unwrap_or(0)
+    }
+}
+
+/// A type together with the types deriving from it, as returned by
+/// [`SourcetrailDB::get_type_hierarchy`].
+#[derive(Debug, Clone)]
+pub struct TypeHierarchyNode {
+    node: Node,
+    children: Vec<TypeHierarchyNode>,
+}
+
+impl TypeHierarchyNode {
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    pub fn children(&self) -> &[TypeHierarchyNode] {
+        &self.children
+    }
+
+    fn build(
+        id: i64,
+        nodes: &HashMap<i64, Node>,
+        derived: &HashMap<i64, Vec<i64>>,
+        path: &mut Vec<i64>,
+    ) -> Result<Self, SourcetrailError> {
+        if path.contains(&id) {
+            path.push(id);
+            let cycle = path
+                .iter()
+                .map(i64::to_string)
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(SourcetrailError::Builder(anyhow::Error::msg(format!(
+                "inheritance cycle: {cycle}"
+            ))));
+        }
+
+        path.push(id);
+        let children = derived[&id]
+            .iter()
+            .map(|child| Self::build(*child, nodes, derived, path))
+            .collect::<Result<_, _>>()?;
+        path.pop();
+
+        Ok(Self {
+            node: nodes[&id].clone(),
+            children,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenOptions {
+    max_connections: u32,
+    journal_mode: Option<JournalMode>,
+    synchronous: Option<Synchronous>,
+    busy_timeout: Option<Duration>,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 5,
+            journal_mode: None,
+            synchronous: None,
+            busy_timeout: None,
+        }
+    }
+}
+
+impl OpenOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// WAL journal, `synchronous = NORMAL` and a 30 second busy timeout; suited
+    /// to indexers writing large amounts of data.
+    pub fn bulk_write() -> Self {
+        Self::default()
+            .journal_mode(JournalMode::Wal)
+            .synchronous(Synchronous::Normal)
+            .busy_timeout(Duration::from_secs(30))
+    }
+
+    pub fn get_max_connections(&self) -> u32 {
+        self.max_connections
+    }
+
+    pub fn set_max_connections(&mut self, max_connections: u32) {
+        self.max_connections = max_connections;
+    }
+
+    pub fn max_connections(mut self, max_connections: u32) -> Self {
+        self.set_max_connections(max_connections);
+        self
+    }
+
+    pub fn get_journal_mode(&self) -> Option<JournalMode> {
+        self.journal_mode
+    }
+
+    pub fn set_journal_mode(&mut self, mode: impl Into<Option<JournalMode>>) {
+        self.journal_mode = mode.into();
+    }
+
+    pub fn journal_mode(mut self, mode: impl Into<Option<JournalMode>>) -> Self {
+        self.set_journal_mode(mode);
+        self
+    }
+
+    pub fn get_synchronous(&self) -> Option<Synchronous> {
+        self.synchronous
+    }
+
+    pub fn set_synchronous(&mut self, synchronous: impl Into<Option<Synchronous>>) {
+        self.synchronous = synchronous.into();
+    }
+
+    pub fn synchronous(mut self, synchronous: impl Into<Option<Synchronous>>) -> Self {
+        self.set_synchronous(synchronous);
+        self
+    }
+
+    pub fn get_busy_timeout(&self) -> Option<Duration> {
+        self.busy_timeout
+    }
+
+    pub fn set_busy_timeout(&mut self, timeout: impl Into<Option<Duration>>) {
+        self.busy_timeout = timeout.into();
+    }
+
+    pub fn busy_timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.set_busy_timeout(timeout);
+        self
+    }
+}
+
+/// Scope guard returned by [`SourcetrailDB::transaction`]. It dereferences
+/// to the database, so all `record_*` methods run inside the transaction;
+/// dropping it without calling [`DbTransaction::commit`] rolls back.
+pub struct DbTransaction<'a> {
+    db: &'a mut SourcetrailDB,
+    finished: bool,
+}
+
+impl DbTransaction<'_> {
+    pub async fn commit(mut self) -> Result<(), SourcetrailError> {
+        self.finished = true;
+        self.db.commit_transaction().await
+    }
+
+    pub async fn rollback(mut self) -> Result<(), SourcetrailError> {
+        self.finished = true;
+        self.db.rollback_transaction().await
+    }
+}
+
+impl Deref for DbTransaction<'_> {
+    type Target = SourcetrailDB;
+
+    fn deref(&self) -> &Self::Target {
+        self.db
+    }
+}
+
+impl DerefMut for DbTransaction<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.db
+    }
+}
+
+impl Drop for DbTransaction<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        // sqlx issues the rollback when a transaction is dropped unfinished
+        if let Some(mut transaction) = self.db.transaction.try_lock() {
+            transaction.take();
+        }
+        self.db.restore_name_cache();
+    }
+}
+
+// parent lookups made by `SourcetrailDB::parent_hierarchy` on this thread
+#[cfg(test)]
+thread_local! {
+    static PARENT_FETCHES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
 pub struct SourcetrailDB {
     database: SqlitePool,
     path: PathBuf,
     name_cache: HashMap<String, i64>,
+    transaction: SharedTransaction,
+    // name cache as it was when the active transaction began
+    name_cache_snapshot: Option<HashMap<String, i64>>,
+    default_delimiter: String,
 }
 
 impl SourcetrailDB {
+    // Columns Sourcetrail reads, per table, with their declared types.
+    const SCHEMA: &'static [(&'static str, &'static [(&'static str, &'static str)])] = &[
+        ("element", &[("id", "INTEGER")]),
+        (
+            "element_component",
+            &[
+                ("id", "INTEGER"),
+                ("element_id", "INTEGER"),
+                ("type", "INTEGER"),
+                ("data", "TEXT"),
+            ],
+        ),
+        (
+            "edge",
+            &[
+                ("id", "INTEGER"),
+                ("type", "INTEGER"),
+                ("source_node_id", "INTEGER"),
+                ("target_node_id", "INTEGER"),
+            ],
+        ),
+        (
+            "node",
+            &[
+                ("id", "INTEGER"),
+                ("type", "INTEGER"),
+                ("serialized_name", "TEXT"),
+            ],
+        ),
+        (
+            "symbol",
+            &[("id", "INTEGER"), ("definition_kind", "INTEGER")],
+        ),
+        (
+            "file",
+            &[
+                ("id", "INTEGER"),
+                ("path", "TEXT"),
+                ("language", "TEXT"),
+                ("modification_time", "TEXT"),
+                ("indexed", "BOOLEAN"),
+                ("complete", "BOOLEAN"),
+                ("line_count", "INTEGER"),
+            ],
+        ),
+        ("filecontent", &[("id", "INTEGER"), ("content", "TEXT")]),
+        ("local_symbol", &[("id", "INTEGER"), ("name", "TEXT")]),
+        (
+            "source_location",
+            &[
+                ("id", "INTEGER"),
+                ("file_node_id", "INTEGER"),
+                ("start_line", "INTEGER"),
+                ("start_column", "INTEGER"),
+                ("end_line", "INTEGER"),
+                ("end_column", "INTEGER"),
+                ("type", "INTEGER"),
+            ],
+        ),
+        (
+            "occurrence",
+            &[("element_id", "INTEGER"), ("source_location_id", "INTEGER")],
+        ),
+        (
+            "component_access",
+            &[("node_id", "INTEGER"), ("type", "INTEGER")],
+        ),
+        (
+            "error",
+            &[
+                ("id", "INTEGER"),
+                ("message", "TEXT"),
+                ("fatal", "BOOLEAN"),
+                ("indexed", "BOOLEAN"),
+                ("translation_unit", "TEXT"),
+            ],
+        ),
+        (
+            "meta",
+            &[("id", "INTEGER"), ("key", "TEXT"), ("value", "TEXT")],
+        ),
+    ];
+
+    const STORAGE_VERSION: i64 = 25;
     const SOURCETRAIL_PROJECT_EXT: &'static str = "srctrlprj";
     const SOURCETRAIL_DB_EXT: &'static str = "srctrldb";
-    const SOURCETRAIL_XML: &'static str = r#"<?xml version="1.0" encoding="utf-8"?>
-<config>
-    <version>0</version>
-</config>"#;
 
     pub fn new(database: SqlitePool, path: PathBuf) -> Self {
         SourcetrailDB {
             database,
             path,
             name_cache: HashMap::new(),
+            transaction: SharedTransaction::default(),
+            name_cache_snapshot: None,
+            default_delimiter: NameHierarchy::NAME_DELIMITER_CXX.to_owned(),
         }
     }
 
@@ -655,6 +1285,10 @@ impl SourcetrailDB {
         &self.path
     }
 
+    async fn executor(&self) -> Executor<'_> {
+        Executor::new(&self.database, &self.transaction).await
+    }
+
     fn uniformize_path(path: &Path) -> PathBuf {
         let mut path = path.to_path_buf();
         if path.extension().unwrap_or_default() != Self::SOURCETRAIL_DB_EXT {
@@ -668,6 +1302,14 @@ impl SourcetrailDB {
     }
 
     pub async fn open(path: impl AsRef<Path>, clear: bool) -> Result<Self, SourcetrailError> {
+        Self::open_with_options(path, clear, OpenOptions::default()).await
+    }
+
+    pub async fn open_with_options(
+        path: impl AsRef<Path>,
+        clear: bool,
+        options: OpenOptions,
+    ) -> Result<Self, SourcetrailError> {
         let path = Self::uniformize_path(path.as_ref());
         if !path.exists() {
             if !clear {
@@ -676,11 +1318,13 @@ impl SourcetrailDB {
                     path.display()
                 )));
             }
-            return Self::create(&path).await;
+            return Self::create_with_options(&path, options).await;
         }
 
-        let database = SqliteHelper::connect(path.to_string_lossy().as_ref()).await?;
+        let database =
+            SqliteHelper::connect_with_options(path.to_string_lossy().as_ref(), &options).await?;
         let db = SourcetrailDB::new(database, path);
+        db.check_storage_version().await?;
 
         if clear {
             db.clear().await?;
@@ -689,7 +1333,51 @@ impl SourcetrailDB {
         Ok(db)
     }
 
+    // Databases without a meta table are left to `verify_schema`.
+    async fn check_storage_version(&self) -> Result<(), SourcetrailError> {
+        if SqliteHelper::table_columns(&self.database, "meta")
+            .await?
+            .is_empty()
+        {
+            return Ok(());
+        }
+
+        match self.get_storage_version().await? {
+            Some(found) if found != Self::STORAGE_VERSION => {
+                Err(SourcetrailError::UnsupportedStorageVersion {
+                    found,
+                    expected: Self::STORAGE_VERSION,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub async fn create(path: impl AsRef<Path>) -> Result<Self, SourcetrailError> {
+        Self::create_with_options(path, OpenOptions::default()).await
+    }
+
+    pub async fn create_with_options(
+        path: impl AsRef<Path>,
+        options: OpenOptions,
+    ) -> Result<Self, SourcetrailError> {
+        Self::create_with_options_and_settings(path, options, &ProjectSettings::default()).await
+    }
+
+    /// Creates a database whose project file and `project_settings` meta
+    /// entry are populated from `settings` rather than left empty.
+    pub async fn create_with_settings(
+        path: impl AsRef<Path>,
+        settings: ProjectSettings,
+    ) -> Result<Self, SourcetrailError> {
+        Self::create_with_options_and_settings(path, OpenOptions::default(), &settings).await
+    }
+
+    async fn create_with_options_and_settings(
+        path: impl AsRef<Path>,
+        options: OpenOptions,
+        settings: &ProjectSettings,
+    ) -> Result<Self, SourcetrailError> {
         let path = Self::uniformize_path(path.as_ref());
         if path.exists() {
             return Err(SourcetrailError::File(format!(
@@ -698,571 +1386,5413 @@ impl SourcetrailDB {
             )));
         }
 
-        let database = SqliteHelper::connect(path.to_string_lossy().as_ref()).await?;
+        let database =
+            SqliteHelper::connect_with_options(path.to_string_lossy().as_ref(), &options).await?;
         let db = SourcetrailDB::new(database, path.clone());
 
         db.create_sql_tables().await?;
 
-        MetaDAO::new(&db.database, "storage_version", "25").await?;
-        MetaDAO::new(&db.database, "project_settings", Self::SOURCETRAIL_XML).await?;
+        MetaDAO::new(
+            &db.database,
+            "storage_version",
+            Self::STORAGE_VERSION.to_string(),
+        )
+        .await?;
+        MetaDAO::new(&db.database, "project_settings", "").await?;
 
-        let project_file = path.with_extension(Self::SOURCETRAIL_PROJECT_EXT);
-        fs::write(&project_file, Self::SOURCETRAIL_XML)?;
+        db.set_project_settings(settings).await?;
 
         Ok(db)
     }
 
-    async fn create_sql_tables(&self) -> Result<(), SourcetrailError> {
-        ElementDAO::create_table(&self.database).await?;
-        ElementComponentDAO::create_table(&self.database).await?;
-        EdgeDAO::create_table(&self.database).await?;
-        NodeDAO::create_table(&self.database).await?;
-        SymbolDAO::create_table(&self.database).await?;
-        FileDAO::create_table(&self.database).await?;
-        FileContentDAO::create_table(&self.database).await?;
-        LocalSymbolDAO::create_table(&self.database).await?;
-        SourceLocationDAO::create_table(&self.database).await?;
-        OccurrenceDAO::create_table(&self.database).await?;
-        ComponentAccessDAO::create_table(&self.database).await?;
-        ErrorDAO::create_table(&self.database).await?;
-        MetaDAO::create_table(&self.database).await?;
-        Ok(())
+    pub async fn meta(&self, key: impl AsRef<str>) -> Result<Option<String>, SourcetrailError> {
+        Ok(
+            MetaDAO::get_by_key(&mut self.executor().await, key.as_ref())
+                .await?
+                .map(|meta| meta.value().to_owned()),
+        )
     }
 
-    pub async fn clear(&self) -> Result<(), SourcetrailError> {
-        ElementDAO::clear(&self.database).await?;
-        ElementComponentDAO::clear(&self.database).await?;
-        EdgeDAO::clear(&self.database).await?;
-        NodeDAO::clear(&self.database).await?;
-        SymbolDAO::clear(&self.database).await?;
-        FileDAO::clear(&self.database).await?;
-        FileContentDAO::clear(&self.database).await?;
-        LocalSymbolDAO::clear(&self.database).await?;
-        SourceLocationDAO::clear(&self.database).await?;
-        OccurrenceDAO::clear(&self.database).await?;
-        ComponentAccessDAO::clear(&self.database).await?;
-        ErrorDAO::clear(&self.database).await?;
-        Ok(())
+    pub async fn get_meta_value(&self, key: &str) -> Result<Option<String>, SourcetrailError> {
+        self.meta(key).await
     }
 
-    pub async fn close(&self) -> Result<(), SourcetrailError> {
-        self.database.close().await;
+    pub async fn set_meta_value(&mut self, key: &str, value: &str) -> Result<(), SourcetrailError> {
+        MetaDAO::upsert(&mut self.executor().await, key, value).await?;
         Ok(())
     }
 
-    async fn add_if_not_existing(
-        &mut self,
-        name: impl AsRef<str>,
-        type_: NodeType,
-    ) -> Result<i64, SourcetrailError> {
-        let name = name.as_ref();
+    /// Checks that every table and column Sourcetrail expects exists with the
+    /// expected type, e.g. to catch a path pointing at a foreign SQLite file.
+    pub async fn verify_schema(&self) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
 
-        if !self.name_cache.contains_key(name) {
-            let elem_id = ElementDAO::new(&self.database).await?;
-            NodeDAO::new(&self.database, &Node::new(elem_id, type_, name)).await?;
-            self.name_cache.insert(name.to_owned(), elem_id);
-            Ok(elem_id)
-        } else {
-            Ok(*self.name_cache.get(name).expect("exists"))
+        for (table, expected) in Self::SCHEMA {
+            let columns = SqliteHelper::table_columns(&mut executor, table).await?;
+            if columns.is_empty() {
+                return Err(SourcetrailError::SchemaMismatch(format!(
+                    "missing table {table}"
+                )));
+            }
+
+            for (column, type_) in *expected {
+                match columns.iter().find(|(name, _)| name == column) {
+                    None => {
+                        return Err(SourcetrailError::SchemaMismatch(format!(
+                            "missing column {table}.{column}"
+                        )))
+                    }
+                    Some((_, found)) if !found.eq_ignore_ascii_case(type_) => {
+                        return Err(SourcetrailError::SchemaMismatch(format!(
+                            "column {table}.{column} has type {found}, expected {type_}"
+                        )))
+                    }
+                    Some(_) => (),
+                }
+            }
         }
+
+        Ok(())
     }
 
-    pub fn record_node<'a, 'b>(&'a mut self, kind: NodeType) -> NodeRecorder<'a, 'b> {
-        NodeRecorder::new(self, kind)
+    pub async fn get_storage_version(&self) -> Result<Option<i64>, SourcetrailError> {
+        self.get_meta_value("storage_version")
+            .await?
+            .map(|version| version.parse().map_err(SourcetrailError::convert))
+            .transpose()
     }
 
-    async fn record_symbol(&mut self, hierarchy: &NameHierarchy) -> Result<i64, SourcetrailError> {
-        let mut ids = vec![];
-        for i in 0..hierarchy.size() {
-            ids.push(
-                self.add_if_not_existing(
-                    &hierarchy.serialize_range(0, i + 1)?,
-                    NodeType::NodeSymbol,
-                )
-                .await?,
+    pub async fn get_project_settings(&self) -> Result<Option<String>, SourcetrailError> {
+        self.get_meta_value("project_settings").await
+    }
+
+    pub fn project_path(&self) -> PathBuf {
+        self.path.with_extension(Self::SOURCETRAIL_PROJECT_EXT)
+    }
+
+    /// Writes a consistent copy of the committed database, together with its
+    /// project file, to `dest` without blocking readers. The copy is written
+    /// next to `dest` first and only replaces an existing backup once it is
+    /// complete.
+    pub async fn backup(
+        &self,
+        dest: impl AsRef<Path>,
+        overwrite: bool,
+    ) -> Result<(), SourcetrailError> {
+        let dest = Self::uniformize_path(dest.as_ref());
+        let dest_project = dest.with_extension(Self::SOURCETRAIL_PROJECT_EXT);
+
+        let is_self = dest == self.path
+            || matches!(
+                (fs::canonicalize(&dest), fs::canonicalize(&self.path)),
+                (Ok(dest), Ok(path)) if dest == path
             );
+        if is_self {
+            return Err(SourcetrailError::File(format!(
+                "{} is the database being backed up",
+                dest.display()
+            )));
         }
 
-        for pair in ids.windows(2) {
-            let parent = pair[0];
-            let child = pair[1];
-            let elem_id = ElementDAO::new(&self.database).await?;
-            EdgeDAO::new(
-                &self.database,
-                Edge::new(elem_id, EdgeType::Member, parent, child),
-            )
-            .await?;
+        if !overwrite {
+            if let Some(existing) = [&dest, &dest_project].into_iter().find(|p| p.exists()) {
+                return Err(SourcetrailError::File(format!(
+                    "{} already exists",
+                    existing.display()
+                )));
+            }
         }
 
-        Ok(*ids.last().expect("at least one id"))
+        let mut tmp_name = dest.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = dest.with_file_name(tmp_name);
+        if tmp.exists() {
+            fs::remove_file(&tmp)?;
+        }
+
+        if let Err(e) = SqliteHelper::vacuum_into(&self.database, &tmp.to_string_lossy()).await {
+            let _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+        fs::rename(&tmp, &dest)?;
+
+        let project = self.project_path();
+        if project.exists() {
+            fs::copy(project, dest_project)?;
+        }
+
+        Ok(())
     }
 
-    async fn full_record_node(
-        &mut self,
-        name: impl AsRef<str>,
-        prefix: impl AsRef<str>,
-        postfix: impl AsRef<str>,
-        delimiter: impl AsRef<str>,
-        parent_id: impl Into<Option<i64>>,
-        is_indexed: bool,
-        node_type: NodeType,
-    ) -> Result<i64, SourcetrailError> {
-        let name_element = NameElement::builder()
-            .prefix(prefix.as_ref())
-            .name(name.as_ref())
-            .postfix(postfix.as_ref())
-            .build();
+    pub async fn set_project_settings(
+        &self,
+        settings: &ProjectSettings,
+    ) -> Result<(), SourcetrailError> {
+        let xml = settings.to_xml();
+        MetaDAO::update_by_key(&mut self.executor().await, "project_settings", &xml).await?;
+        fs::write(self.project_path(), &xml)?;
+        Ok(())
+    }
 
-        let obj_id = if let Some(parent_id) = parent_id.into() {
-            let node = NodeDAO::get(&self.database, parent_id)
-                .await?
-                .ok_or(SourcetrailError::ParentNotFound(parent_id))?;
-            let mut hierarchy = NameHierarchy::deserialize_name(node.name())?;
-            hierarchy.push_element(name_element);
-            self.record_symbol(&hierarchy).await?
-        } else {
-            self.record_symbol(&NameHierarchy::new(delimiter.as_ref(), [name_element])?)
-                .await?
-        };
+    async fn create_sql_tables(&self) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
+        ElementDAO::create_table(&mut executor).await?;
+        ElementComponentDAO::create_table(&mut executor).await?;
+        EdgeDAO::create_table(&mut executor).await?;
+        NodeDAO::create_table(&mut executor).await?;
+        SymbolDAO::create_table(&mut executor).await?;
+        FileDAO::create_table(&mut executor).await?;
+        FileContentDAO::create_table(&mut executor).await?;
+        LocalSymbolDAO::create_table(&mut executor).await?;
+        SourceLocationDAO::create_table(&mut executor).await?;
+        OccurrenceDAO::create_table(&mut executor).await?;
+        ComponentAccessDAO::create_table(&mut executor).await?;
+        ErrorDAO::create_table(&mut executor).await?;
+        MetaDAO::create_table(&mut executor).await?;
+        Ok(())
+    }
+
+    pub async fn clear(&self) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
+        ElementDAO::clear(&mut executor).await?;
+        ElementComponentDAO::clear(&mut executor).await?;
+        EdgeDAO::clear(&mut executor).await?;
+        NodeDAO::clear(&mut executor).await?;
+        SymbolDAO::clear(&mut executor).await?;
+        FileDAO::clear(&mut executor).await?;
+        FileContentDAO::clear(&mut executor).await?;
+        LocalSymbolDAO::clear(&mut executor).await?;
+        SourceLocationDAO::clear(&mut executor).await?;
+        OccurrenceDAO::clear(&mut executor).await?;
+        ComponentAccessDAO::clear(&mut executor).await?;
+        ErrorDAO::clear(&mut executor).await?;
+        Ok(())
+    }
 
-        self.record_symbol_kind(obj_id, node_type).await?;
+    pub async fn close(&self) -> Result<(), SourcetrailError> {
+        // an open transaction holds on to a pooled connection
+        self.transaction.lock().await.take();
+        self.database.close().await;
+        Ok(())
+    }
 
-        if is_indexed {
-            self.record_symbol_definition_kind(obj_id, SymbolType::Explicit)
-                .await?;
+    pub async fn begin_transaction(&mut self) -> Result<(), SourcetrailError> {
+        let mut transaction = self.transaction.lock().await;
+        if transaction.is_some() {
+            return Err(SourcetrailError::TransactionActive);
         }
 
-        Ok(obj_id)
+        *transaction = Some(self.database.begin().await?);
+        self.name_cache_snapshot = Some(self.name_cache.clone());
+
+        Ok(())
     }
 
-    async fn record_symbol_kind(&self, id: i64, type_: NodeType) -> Result<(), SourcetrailError> {
-        if let Some(mut node) = NodeDAO::get(&self.database, id).await? {
-            node.set_type(type_);
-            NodeDAO::update(&self.database, node).await?;
+    /// Begins a transaction scoped to the returned guard, e.g. to record a
+    /// node, its locations and its edges atomically.
+    pub async fn transaction(&mut self) -> Result<DbTransaction<'_>, SourcetrailError> {
+        self.begin_transaction().await?;
+        Ok(DbTransaction {
+            db: self,
+            finished: false,
+        })
+    }
+
+    pub async fn commit_transaction(&mut self) -> Result<(), SourcetrailError> {
+        let tx = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or(SourcetrailError::NoTransaction)?;
+
+        if let Err(e) = tx.commit().await {
+            self.restore_name_cache();
+            return Err(e.into());
         }
+
+        self.name_cache_snapshot = None;
         Ok(())
     }
 
-    async fn record_symbol_definition_kind(
-        &self,
-        id: i64,
-        kind: SymbolType,
-    ) -> Result<(), SourcetrailError> {
-        if let Some(mut sym) = SymbolDAO::get(&self.database, id).await? {
-            if sym.definition_kind() != kind {
-                sym.set_definition_kind(kind);
-                SymbolDAO::update(&self.database, sym).await?;
-            }
-        } else {
-            SymbolDAO::new(&self.database, Symbol::new(id, kind)).await?;
-        }
+    pub async fn rollback_transaction(&mut self) -> Result<(), SourcetrailError> {
+        let tx = self
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or(SourcetrailError::NoTransaction)?;
+        self.restore_name_cache();
+        tx.rollback().await?;
+
         Ok(())
     }
 
-    pub fn record_symbol_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeSymbol)
+    fn restore_name_cache(&mut self) {
+        if let Some(names) = self.name_cache_snapshot.take() {
+            self.name_cache = names;
+        }
     }
 
-    pub fn record_builtin_type_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeBuiltinType)
+    pub async fn nodes_of_type(&self, node_type: NodeType) -> Result<Vec<Node>, SourcetrailError> {
+        NodeDAO::list_by_type(&mut self.executor().await, node_type).await
     }
 
-    pub fn record_module<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeModule)
+    /// Nodes whose serialized name matches the SQL `LIKE` `pattern`, e.g.
+    /// `%MyClass%`. The match runs in SQLite and is case-insensitive for
+    /// ASCII, but sees the serialized form, where name parts are separated by
+    /// tab-prefixed markers rather than the display delimiter.
+    pub async fn list_nodes_matching_pattern(
+        &self,
+        pattern: impl AsRef<str>,
+    ) -> Result<Vec<Node>, SourcetrailError> {
+        NodeDAO::list_by_name_like(&mut self.executor().await, pattern.as_ref()).await
     }
 
-    pub fn record_namespace<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeNamespace)
+    /// Nodes whose display name (e.g. `std::vector<T>::size`) matches
+    /// `regex`. Unlike [`list_nodes_matching_pattern`](Self::list_nodes_matching_pattern)
+    /// this reads and decodes every node, so it is much slower on large
+    /// databases.
+    #[cfg(feature = "regex")]
+    pub async fn list_nodes_matching_regex(
+        &self,
+        regex: &regex::Regex,
+    ) -> Result<Vec<Node>, SourcetrailError> {
+        let mut matching = Vec::new();
+        for node in NodeDAO::list(&mut self.executor().await).await? {
+            let name = NameHierarchy::deserialize_name(node.name())?.to_display_string();
+            if regex.is_match(&name) {
+                matching.push(node);
+            }
+        }
+        Ok(matching)
     }
 
-    pub fn record_package<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodePackage)
+    pub async fn nodes_of_kind_in_file(
+        &self,
+        file_id: i64,
+        kind: NodeType,
+    ) -> Result<Vec<Node>, SourcetrailError> {
+        NodeDAO::list_by_type_in_file(&mut self.executor().await, file_id, kind).await
     }
 
-    pub fn record_struct<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeStruct)
+    /// Streams all nodes without buffering the whole table. The stream reads
+    /// committed data through its own connection.
+    pub fn stream_nodes(&self) -> impl Stream<Item = Result<Node, SourcetrailError>> + '_ {
+        NodeDAO::stream(&self.database)
     }
 
-    pub fn record_class<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeClass)
+    /// Streams all files in id order. Rows that fail to convert (e.g. with an
+    /// unparseable modification time) are yielded as errors and do not end
+    /// the stream.
+    pub fn stream_files(&self) -> impl Stream<Item = Result<File, SourcetrailError>> + '_ {
+        FileDAO::stream(&self.database)
     }
 
-    pub fn record_interface<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeInterface)
+    pub fn stream_edges(&self) -> impl Stream<Item = Result<Edge, SourcetrailError>> + '_ {
+        EdgeDAO::stream(&self.database)
     }
 
-    pub fn record_annotation<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeAnnotation)
+    pub fn stream_errors(&self) -> impl Stream<Item = Result<Error, SourcetrailError>> + '_ {
+        ErrorDAO::stream(&self.database)
     }
 
-    pub fn record_global_variable<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeGlobalVariable)
+    pub fn stream_source_locations(
+        &self,
+    ) -> impl Stream<Item = Result<SourceLocation, SourcetrailError>> + '_ {
+        SourceLocationDAO::stream(&self.database)
     }
 
-    pub fn record_field<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeField)
+    pub async fn node_count(&self) -> Result<i64, SourcetrailError> {
+        NodeDAO::count(&mut self.executor().await).await
     }
 
-    pub fn record_function<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeFunction)
+    pub async fn edge_count(&self) -> Result<i64, SourcetrailError> {
+        EdgeDAO::count(&mut self.executor().await).await
     }
 
-    pub fn record_method<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeMethod)
+    /// Maps each node with at least one non-member edge to its
+    /// `(incoming, outgoing)` edge counts.
+    pub async fn reference_degrees(&self) -> Result<HashMap<i64, (u64, u64)>, SourcetrailError> {
+        Ok(EdgeDAO::degrees(&mut self.executor().await)
+            .await?
+            .into_iter()
+            .map(|(id, incoming, outgoing)| (id, (incoming as u64, outgoing as u64)))
+            .collect())
     }
 
-    pub fn record_enum<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeEnum)
+    pub async fn symbol_count(&self) -> Result<i64, SourcetrailError> {
+        SymbolDAO::count(&mut self.executor().await).await
     }
 
-    pub fn record_enum_constant<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeEnumConstant)
+    pub async fn file_count(&self) -> Result<i64, SourcetrailError> {
+        FileDAO::count(&mut self.executor().await).await
     }
 
-    pub fn record_typedef_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeTypedef)
+    pub async fn stats(&self) -> Result<DbStats, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        Ok(DbStats {
+            nodes: NodeDAO::count(&mut executor).await?.max(0) as u64,
+            edges: EdgeDAO::count(&mut executor).await?.max(0) as u64,
+            files: FileDAO::count(&mut executor).await?.max(0) as u64,
+            symbols: SymbolDAO::count(&mut executor).await?.max(0) as u64,
+            nodes_by_type: NodeDAO::count_by_type(&mut executor)
+                .await?
+                .into_iter()
+                .map(|(node_type, count)| (node_type, count.max(0) as u64))
+                .collect(),
+        })
     }
 
-    pub fn record_type_parameter_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeTypeParameter)
+    pub async fn total_line_count(&self) -> Result<u64, SourcetrailError> {
+        Ok(FileDAO::total_line_count(&mut self.executor().await)
+            .await?
+            .max(0) as u64)
     }
 
-    pub fn record_type_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeType)
+    pub async fn checkpoint(&self) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec_raw(
+            &mut self.executor().await,
+            "PRAGMA wal_checkpoint(TRUNCATE);",
+        )
+        .await
     }
 
-    pub fn record_macro<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeMacro)
+    /// Refreshes the query planner statistics and compacts the database.
+    ///
+    /// `VACUUM` needs exclusive access to the database file: do not call this
+    /// while other connections to it are open.
+    pub async fn optimize(&self) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
+        if matches!(executor, Executor::Transaction(_)) {
+            return Err(SourcetrailError::TransactionActive);
+        }
+
+        SqliteHelper::exec_raw(&mut executor, "PRAGMA analysis_limit=1000;").await?;
+        SqliteHelper::exec_raw(&mut executor, "ANALYZE;").await?;
+        SqliteHelper::exec_raw(&mut executor, "VACUUM;").await
     }
 
-    pub fn record_union<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
-        self.record_node(NodeType::NodeUnion)
+    /// Rebuilds the database file to reclaim free pages.
+    ///
+    /// `VACUUM` needs exclusive access to the database file: do not call this
+    /// while other connections to it are open.
+    pub async fn vacuum(&self) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
+        if matches!(executor, Executor::Transaction(_)) {
+            return Err(SourcetrailError::TransactionActive);
+        }
+
+        SqliteHelper::exec_raw(&mut executor, "VACUUM;").await
     }
 
-    async fn record_reference(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
-        edge_type: EdgeType,
-    ) -> Result<i64, SourcetrailError> {
-        let elem_id = ElementDAO::new(&self.database).await?;
-        EdgeDAO::new(
-            &self.database,
-            Edge::new(elem_id, edge_type, source_id, target_id),
-        )
-        .await?;
-        Ok(elem_id)
+    pub async fn defragment(&self) -> Result<(), SourcetrailError> {
+        self.vacuum().await
     }
 
-    pub async fn record_ref_member(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
-    ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::Member)
-            .await
+    /// Deletes a node together with its members, recursively, and every
+    /// edge and occurrence of the deleted nodes.
+    pub async fn delete_node(&mut self, node_id: i64) -> Result<(), SourcetrailError> {
+        let deleted = {
+            let mut executor = self.executor().await;
+            let mut tx = executor.begin().await?;
+
+            let deleted = Self::delete_node_tree(&mut tx, node_id).await?;
+
+            tx.commit().await?;
+            deleted
+        };
+
+        self.name_cache.retain(|_, id| !deleted.contains(id));
+        Ok(())
     }
 
-    pub async fn record_ref_type_usage(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
-    ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::TypeUsage)
-            .await
+    async fn delete_node_tree(
+        conn: &mut SqliteConnection,
+        node_id: i64,
+    ) -> Result<Vec<i64>, SourcetrailError> {
+        if NodeDAO::get(&mut *conn, node_id).await?.is_none() {
+            return Err(SourcetrailError::NodeNotFound(node_id));
+        }
+
+        let mut ids = vec![node_id];
+        let mut next = 0;
+        while next < ids.len() {
+            for member in EdgeDAO::list_by_source(&mut *conn, ids[next], EdgeType::Member).await? {
+                ids.push(member.target_id());
+            }
+            next += 1;
+        }
+
+        for id in &ids {
+            EdgeDAO::delete_for_node(&mut *conn, *id).await?;
+            ElementDAO::delete(&mut *conn, *id).await?;
+        }
+        SourceLocationDAO::delete_unreferenced(&mut *conn).await?;
+
+        Ok(ids)
     }
 
-    pub async fn record_ref_usage(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
-    ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::Usage)
-            .await
+    pub async fn delete_file(&mut self, file_id: i64) -> Result<(), SourcetrailError> {
+        let deleted = {
+            let mut executor = self.executor().await;
+            if FileDAO::get(&mut executor, file_id).await?.is_none() {
+                return Err(SourcetrailError::FileNotFound(file_id));
+            }
+
+            let mut tx = executor.begin().await?;
+
+            FileContentDAO::delete(&mut *tx, file_id).await?;
+            FileDAO::delete(&mut *tx, file_id).await?;
+            let deleted = Self::delete_node_tree(&mut tx, file_id).await?;
+
+            tx.commit().await?;
+            deleted
+        };
+
+        self.name_cache.retain(|_, id| !deleted.contains(id));
+        Ok(())
     }
 
-    pub async fn record_ref_call(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
+    async fn add_if_not_existing(
+        name_cache: &mut HashMap<String, i64>,
+        conn: impl Acquire<'_, Database = Sqlite>,
+        name: impl AsRef<str>,
+        type_: NodeType,
     ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::Call)
-            .await
+        let name = name.as_ref();
+
+        if !name_cache.contains_key(name) {
+            let mut conn = conn.acquire().await?;
+            let elem_id = ElementDAO::new(&mut *conn).await?;
+            NodeDAO::new(&mut *conn, &Node::new(elem_id, type_, name)).await?;
+            name_cache.insert(name.to_owned(), elem_id);
+            Ok(elem_id)
+        } else {
+            Ok(*name_cache.get(name).expect("exists"))
+        }
     }
 
-    pub async fn record_ref_inheritance(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
-    ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::Inheritance)
-            .await
+    pub fn record_node<'a, 'b>(&'a mut self, kind: NodeType) -> NodeRecorder<'a, 'b> {
+        NodeRecorder::new(self, kind)
     }
 
-    pub async fn record_ref_override(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
-    ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::Override)
-            .await
+    pub fn default_delimiter(&self) -> &str {
+        &self.default_delimiter
     }
 
-    pub async fn record_ref_type_argument(
+    /// Sets the delimiter used by node recorders that do not set their own,
+    /// e.g. `NameHierarchy::NAME_DELIMITER_JAVA`. Defaults to `::`.
+    pub fn set_default_delimiter(&mut self, delimiter: impl Into<String>) {
+        self.default_delimiter = delimiter.into();
+    }
+
+    async fn record_symbol(
         &mut self,
-        source_id: i64,
-        target_id: i64,
+        hierarchy: &NameHierarchy,
+        intermediate_type: NodeType,
     ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::TypeArgument)
-            .await
+        let mut ids = vec![];
+        for i in 0..hierarchy.size() {
+            let node_type = if i + 1 < hierarchy.size() {
+                intermediate_type
+            } else {
+                NodeType::NodeSymbol
+            };
+            ids.push(
+                Self::add_if_not_existing(
+                    &mut self.name_cache,
+                    &mut Executor::new(&self.database, &self.transaction).await,
+                    &hierarchy.serialize_range(0, i + 1)?,
+                    node_type,
+                )
+                .await?,
+            );
+        }
+
+        for pair in ids.windows(2) {
+            let parent = pair[0];
+            let child = pair[1];
+            let elem_id = ElementDAO::new(&mut self.executor().await).await?;
+            EdgeDAO::new(
+                &mut self.executor().await,
+                Edge::new(elem_id, EdgeType::Member, parent, child),
+            )
+            .await?;
+        }
+
+        Ok(*ids.last().expect("at least one id"))
     }
 
-    pub async fn record_ref_template_specialization(
+    /// Records the leaf of `hierarchy` as `node_type`. Missing intermediate
+    /// levels (e.g. `a` and `a::b` for `a::b::C`) are created as
+    /// `intermediate_type`, without a symbol row, i.e. as `SymbolType::None`.
+    pub async fn record_symbol_hierarchy(
         &mut self,
-        source_id: i64,
-        target_id: i64,
+        hierarchy: &NameHierarchy,
+        node_type: NodeType,
+        intermediate_type: NodeType,
     ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::TemplateSpecialization)
-            .await
+        let id = self.record_symbol(hierarchy, intermediate_type).await?;
+
+        let mut executor = self.executor().await;
+        Self::record_symbol_kind(&mut executor, id, node_type).await?;
+        Self::record_symbol_definition_kind(&mut executor, id, SymbolType::Explicit).await?;
+
+        Ok(id)
     }
 
-    pub async fn record_ref_include(
+    /// Records the node named by `qualified_path`, e.g. `a::b::C`, as `kind`.
+    /// Missing parents are recorded as `NodeType::NodeSymbol`.
+    pub async fn record_node_with_path(
         &mut self,
-        source_id: i64,
-        target_id: i64,
+        delimiter: &str,
+        qualified_path: &str,
+        kind: NodeType,
     ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::Include)
+        let hierarchy = NameHierarchy::from_qualified_name(qualified_path, delimiter)?;
+        self.record_symbol_hierarchy(&hierarchy, kind, NodeType::NodeSymbol)
             .await
     }
 
-    pub async fn record_ref_import(
+    /// Records a symbol together with one location in a single transaction
+    /// (or inside the active one). Sourcetrail has no per-node language, so
+    /// `language`, if given, is set on the file containing `location`.
+    pub async fn record_symbol_at(
         &mut self,
-        source_id: i64,
-        target_id: i64,
+        hierarchy: &NameHierarchy,
+        node_type: NodeType,
+        location: SourceLocation,
+        language: Option<&str>,
     ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::Import)
-            .await
+        let owns_transaction = self.transaction.lock().await.is_none();
+        if owns_transaction {
+            self.begin_transaction().await?;
+        }
+
+        let file_id = location.file_node_id();
+        let result = async {
+            let id = self
+                .record_symbol_hierarchy(hierarchy, node_type, NodeType::NodeSymbol)
+                .await?;
+            self.record_source_locations(&[(id, location)]).await?;
+            if let Some(language) = language {
+                self.record_file_language(file_id, language).await?;
+            }
+            Ok(id)
+        }
+        .await;
+
+        if owns_transaction {
+            if result.is_ok() {
+                self.commit_transaction().await?;
+            } else {
+                self.rollback_transaction().await?;
+            }
+        }
+
+        result
     }
 
-    pub async fn record_ref_bundled_edges(
+    #[allow(clippy::too_many_arguments)]
+    async fn full_record_node(
         &mut self,
-        source_id: i64,
-        target_id: i64,
+        name: impl AsRef<str>,
+        prefix: impl AsRef<str>,
+        postfix: impl AsRef<str>,
+        delimiter: impl AsRef<str>,
+        parent_id: impl Into<Option<i64>>,
+        is_indexed: bool,
+        node_type: NodeType,
+        strict_parent: bool,
     ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::BundledEdges)
-            .await
+        let name_element = NameElement::builder()
+            .prefix(prefix.as_ref())
+            .name(name.as_ref())
+            .postfix(postfix.as_ref())
+            .build();
+
+        let obj_id = if let Some(parent_id) = parent_id.into() {
+            let (mut hierarchy, parent_type) = self.parent_hierarchy(parent_id).await?;
+            if strict_parent && !parent_type.can_contain(node_type) {
+                return Err(SourcetrailError::InvalidParent {
+                    id: parent_id,
+                    parent: parent_type,
+                    child: node_type,
+                });
+            }
+            hierarchy.push_element(name_element);
+            self.record_symbol(&hierarchy, NodeType::NodeSymbol).await?
+        } else {
+            self.record_symbol(
+                &NameHierarchy::new(delimiter.as_ref(), [name_element])?,
+                NodeType::NodeSymbol,
+            )
+            .await?
+        };
+
+        Self::record_symbol_kind(&mut self.executor().await, obj_id, node_type).await?;
+
+        let mut executor = self.executor().await;
+        if is_indexed {
+            Self::record_symbol_definition_kind(&mut executor, obj_id, SymbolType::Explicit)
+                .await?;
+        } else if SymbolDAO::get(&mut executor, obj_id)
+            .await?
+            .is_none_or(|sym| sym.definition_kind() != SymbolType::Explicit)
+        {
+            // never downgrade a symbol already defined elsewhere
+            Self::record_symbol_definition_kind(&mut executor, obj_id, SymbolType::Implicit)
+                .await?;
+        }
+
+        Ok(obj_id)
     }
 
-    pub async fn record_ref_macro_usage(
+    /// Resolves the id of an already recorded node, e.g. one recorded in a
+    /// previous session.
+    pub async fn node_id_by_name(
         &mut self,
-        source_id: i64,
-        target_id: i64,
-    ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::MacroUsage)
-            .await
+        hierarchy: &NameHierarchy,
+    ) -> Result<Option<i64>, SourcetrailError> {
+        let name = hierarchy.serialize_name()?;
+        if let Some(id) = self.name_cache.get(&name) {
+            return Ok(Some(*id));
+        }
+
+        let node = NodeDAO::get_by_name(&mut self.executor().await, &name).await?;
+        Ok(node.map(|node| {
+            self.name_cache.insert(name, node.id());
+            node.id()
+        }))
     }
 
-    pub async fn record_ref_annotation_usage(
-        &mut self,
-        source_id: i64,
-        target_id: i64,
-    ) -> Result<i64, SourcetrailError> {
-        self.record_reference(source_id, target_id, EdgeType::AnnotationUsage)
-            .await
+    /// Resolves many names at once; the result is aligned with `hierarchies`
+    /// and holds `None` for names that are not recorded.
+    pub async fn resolve_names(
+        &self,
+        hierarchies: &[NameHierarchy],
+    ) -> Result<Vec<Option<i64>>, SourcetrailError> {
+        let names = hierarchies
+            .iter()
+            .map(NameHierarchy::serialize_name)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ids = NodeDAO::list_by_names(&mut self.executor().await, &names)
+            .await?
+            .into_iter()
+            .map(|node| (node.name().to_owned(), node.id()))
+            .collect::<HashMap<_, _>>();
+
+        Ok(names.iter().map(|name| ids.get(name).copied()).collect())
     }
 
-    pub fn record_reference_to_unsolved_symbol<'a>(&'a mut self) -> UnsolvedSymbolRecorder<'a> {
-        UnsolvedSymbolRecorder::new(self)
+    /// Maps each of the qualified `names`, split on `delimiter`, that has a
+    /// recorded node to that node's id; missing names are left out.
+    pub async fn existing_node_ids_for(
+        &self,
+        names: &[String],
+        delimiter: &str,
+    ) -> Result<HashMap<String, i64>, SourcetrailError> {
+        let hierarchies = names
+            .iter()
+            .map(|name| NameHierarchy::from_qualified_name(name, delimiter))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(names
+            .iter()
+            .zip(self.resolve_names(&hierarchies).await?)
+            .filter_map(|(name, id)| Some((name.clone(), id?)))
+            .collect())
+    }
+
+    pub async fn get_node_hierarchy(
+        &self,
+        node_id: i64,
+    ) -> Result<NameHierarchy, SourcetrailError> {
+        let node = NodeDAO::get(&mut self.executor().await, node_id)
+            .await?
+            .ok_or(SourcetrailError::ParentNotFound(node_id))?;
+        NameHierarchy::deserialize_name(node.name())
     }
 
-    pub async fn record_reference_is_ambiguous(
-        &mut self,
-        reference_id: i64,
-    ) -> Result<(), SourcetrailError> {
-        ElementComponentDAO::new(
-            &self.database,
-            ElementComponent::new(0, reference_id, ElementComponentType::IsAmbiguous, ""),
-        )
-        .await?;
-        Ok(())
+    pub async fn get_qualified_name(&self, node_id: i64) -> Result<String, SourcetrailError> {
+        Ok(self.get_node_hierarchy(node_id).await?.to_display_string())
     }
 
-    pub fn record_file<'a, 'b>(&'a mut self) -> FileRecorder<'a, 'b> {
-        FileRecorder::new(self)
+    /// The node containing `node_id`, i.e. the source of its `Member` edge.
+    pub async fn get_parent_of_node(&self, node_id: i64) -> Result<Option<Node>, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        let Some(edge) = EdgeDAO::list_by_target(&mut executor, node_id, EdgeType::Member)
+            .await?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        NodeDAO::get(&mut executor, edge.source_id()).await
     }
 
-    async fn record_file_with(
+    pub async fn get_children_of_node(&self, node_id: i64) -> Result<Vec<Node>, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        // a child recorded more than once has one member edge per recording
+        let mut seen = HashSet::new();
+        let mut children = Vec::new();
+        for edge in EdgeDAO::list_by_source(&mut executor, node_id, EdgeType::Member).await? {
+            if !seen.insert(edge.target_id()) {
+                continue;
+            }
+            let child = NodeDAO::get(&mut executor, edge.target_id())
+                .await?
+                .ok_or(SourcetrailError::NodeNotFound(edge.target_id()))?;
+            children.push(child);
+        }
+
+        Ok(children)
+    }
+
+    async fn parent_hierarchy(
         &mut self,
-        path: impl AsRef<Path>,
-        modification_time: DateTime<Utc>,
-        content: impl AsRef<str>,
-        indexed: bool,
-    ) -> Result<i64, SourcetrailError> {
-        let path = path.as_ref();
+        parent_id: i64,
+    ) -> Result<(NameHierarchy, NodeType), SourcetrailError> {
+        #[cfg(test)]
+        PARENT_FETCHES.with(|fetches| fetches.set(fetches.get() + 1));
 
-        let hierarchy = NameHierarchy::new(
-            NameHierarchy::NAME_DELIMITER_FILE,
-            [NameElement::builder().name(path.to_string_lossy()).build()],
-        )?;
+        let node = NodeDAO::get(&mut self.executor().await, parent_id)
+            .await?
+            .ok_or(SourcetrailError::ParentNotFound(parent_id))?;
+        Ok((NameHierarchy::deserialize_name(node.name())?, node.type_()))
+    }
 
-        let content = content.as_ref();
+    pub async fn record_members(
+        &mut self,
+        parent_id: i64,
+        members: &[(NodeType, NameElement)],
+    ) -> Result<Vec<i64>, SourcetrailError> {
+        let (parent, _) = self.parent_hierarchy(parent_id).await?;
+
+        let names = members
+            .iter()
+            .map(|(_, element)| {
+                let mut hierarchy = parent.clone();
+                hierarchy.push_element(element.clone());
+                hierarchy.serialize_name()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let fresh = names
+            .iter()
+            .filter(|name| !self.name_cache.contains_key(*name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let result = async {
+            let mut executor = Executor::new(&self.database, &self.transaction).await;
+            let mut tx = executor.begin().await?;
+            let mut ids = Vec::with_capacity(members.len());
+
+            for ((node_type, _), name) in members.iter().zip(&names) {
+                let id = Self::add_if_not_existing(
+                    &mut self.name_cache,
+                    &mut *tx,
+                    name,
+                    NodeType::NodeSymbol,
+                )
+                .await?;
 
-        let lines = if indexed {
-            content.lines().count() as u32
-        } else {
-            0
-        };
+                let elem_id = ElementDAO::new(&mut *tx).await?;
+                EdgeDAO::new(
+                    &mut *tx,
+                    Edge::new(elem_id, EdgeType::Member, parent_id, id),
+                )
+                .await?;
 
-        let elem_id = self
-            .add_if_not_existing(hierarchy.serialize_name()?, NodeType::NodeFile)
-            .await?;
+                Self::record_symbol_kind(&mut *tx, id, *node_type).await?;
+                Self::record_symbol_definition_kind(&mut *tx, id, SymbolType::Explicit).await?;
 
-        FileDAO::new(
-            &self.database,
-            File::builder()
-                .id(elem_id)
-                .path(path)
-                .modification_time(modification_time)
-                .indexed(indexed)
-                .complete(true)
-                .line_count(lines)
-                .build()
-                .map_err(SourcetrailError::builder)?,
-        )
-        .await?;
+                ids.push(id);
+            }
 
-        if indexed {
-            FileContentDAO::new(&self.database, FileContent::new(elem_id, content)).await?;
+            tx.commit().await?;
+            Ok(ids)
         }
+        .await;
 
-        Ok(elem_id)
+        if result.is_err() {
+            for name in fresh {
+                self.name_cache.remove(&name);
+            }
+        }
+
+        result
     }
 
-    pub async fn record_file_language(
-        &mut self,
+    async fn record_symbol_kind(
+        conn: impl Acquire<'_, Database = Sqlite>,
         id: i64,
-        language: impl AsRef<str>,
+        type_: NodeType,
     ) -> Result<(), SourcetrailError> {
-        let mut file = FileDAO::get(&self.database, id)
-            .await?
-            .ok_or(SourcetrailError::FileNotFound(id))?;
-        file.set_language(language.as_ref());
-        FileDAO::update(&self.database, file).await?;
+        let mut conn = conn.acquire().await?;
+        if let Some(mut node) = NodeDAO::get(&mut *conn, id).await? {
+            node.set_type(type_);
+            NodeDAO::update(&mut *conn, node).await?;
+        }
         Ok(())
     }
 
-    async fn record_source_location(
-        &mut self,
-        symbol_id: i64,
-        file_id: i64,
-        start_line: i32,
-        start_column: i32,
-        end_line: i32,
-        end_column: i32,
-        location_type: SourceLocationType,
+    async fn record_symbol_definition_kind(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+        kind: SymbolType,
     ) -> Result<(), SourcetrailError> {
-        let loc_id = SourceLocationDAO::new(
-            &self.database,
-            SourceLocation::new(
-                0,
-                file_id,
-                start_line,
-                start_column,
-                end_line,
-                end_column,
-                location_type,
-            )?,
-        )
-        .await?;
-
-        OccurrenceDAO::new(&self.database, Occurrence::new(symbol_id, loc_id)).await?;
+        let mut conn = conn.acquire().await?;
+        if let Some(mut sym) = SymbolDAO::get(&mut *conn, id).await? {
+            if sym.definition_kind() != kind {
+                sym.set_definition_kind(kind);
+                SymbolDAO::update(&mut *conn, sym).await?;
+            }
+        } else {
+            SymbolDAO::new(&mut *conn, Symbol::new(id, kind)).await?;
+        }
+        Ok(())
+    }
 
+    pub async fn record_access_specifier(
+        &mut self,
+        id: i64,
+        access: ComponentAccessType,
+    ) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
+        if let Some(mut component) = ComponentAccessDAO::get(&mut executor, id).await? {
+            if component.access_type() != access {
+                component.set_access_type(access);
+                ComponentAccessDAO::update(&mut executor, component).await?;
+            }
+        } else {
+            ComponentAccessDAO::new(&mut executor, ComponentAccess::new(id, access)).await?;
+        }
         Ok(())
     }
 
-    pub fn record_location<'a>(
-        &'a mut self,
-        kind: SourceLocationType,
-    ) -> SourceLocationRecorder<'a> {
-        SourceLocationRecorder::new(self, kind)
+    pub fn record_symbol_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeSymbol)
     }
 
-    pub fn record_symbol_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
-        self.record_location(SourceLocationType::Token)
+    pub fn record_builtin_type_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeBuiltinType)
     }
 
-    pub fn record_symbol_scope_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
-        self.record_location(SourceLocationType::Scope)
+    pub fn record_module<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeModule)
     }
 
-    pub fn record_symbol_signature_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
-        self.record_location(SourceLocationType::Signature)
+    pub fn record_namespace<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeNamespace)
     }
 
-    pub fn record_reference_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
-        self.record_location(SourceLocationType::Token)
+    pub fn record_package<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodePackage)
     }
 
-    pub fn record_qualifier_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
-        self.record_location(SourceLocationType::Qualifier)
+    pub fn record_struct<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeStruct)
     }
 
-    pub async fn record_local_symbol(
-        &mut self,
-        name: impl AsRef<str>,
-    ) -> Result<i64, SourcetrailError> {
-        let name = name.as_ref();
-        if let Some(local) = LocalSymbolDAO::get_by_name(&self.database, name).await? {
-            return Ok(local.id());
-        }
+    pub fn record_class<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeClass)
+    }
 
-        let elem_id = ElementDAO::new(&self.database).await?;
-        LocalSymbolDAO::new(&self.database, LocalSymbol::new(elem_id, name)).await?;
-        Ok(elem_id)
+    pub fn record_interface<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeInterface)
     }
 
-    pub fn record_local_symbol_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
-        self.record_location(SourceLocationType::LocalSymbol)
+    pub fn record_annotation<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeAnnotation)
     }
 
-    pub fn record_atomic_source_range<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
-        self.record_location(SourceLocationType::AtomicRange)
+    pub fn record_global_variable<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeGlobalVariable)
     }
 
-    pub fn record_error<'a, 'b>(&'a mut self) -> ErrorRecorder<'a, 'b> {
-        ErrorRecorder::new(self)
+    pub fn record_field<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeField)
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    pub fn record_function<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeFunction)
+    }
 
-    #[async_std::test]
-    #[ignore]
-    async fn test_db() -> Result<(), Box<dyn std::error::Error>> {
-        let mut db = SourcetrailDB::create("test").await?;
+    pub fn record_method<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeMethod)
+    }
 
-        let my_main = db.record_class().name("MyMainClass").commit().await?;
+    pub fn record_child_method_with_access<'a, 'b>(
+        &'a mut self,
+        parent_id: i64,
+        access: ComponentAccessType,
+    ) -> NodeRecorder<'a, 'b> {
+        self.record_method().parent(parent_id).access(access)
+    }
 
-        let meth_id = db
-            .record_method()
-            .name("main")
-            .parent(my_main)
-            .commit()
-            .await?;
+    pub fn record_enum<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeEnum)
+    }
 
-        let class_id = db.record_class().name("PersonalInfo").commit().await?;
+    pub fn record_enum_constant<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeEnumConstant)
+    }
 
+    pub async fn record_enum_with_constants(
+        &mut self,
+        enum_name: &str,
+        constants: &[&str],
+    ) -> Result<(i64, Vec<i64>), SourcetrailError> {
+        let enum_id = self.record_enum().name(enum_name).commit().await?;
+
+        let members = constants
+            .iter()
+            .map(|name| {
+                (
+                    NodeType::NodeEnumConstant,
+                    NameElement::builder().name(*name).build(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let constant_ids = self.record_members(enum_id, &members).await?;
+
+        Ok((enum_id, constant_ids))
+    }
+
+    pub fn record_typedef_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeTypedef)
+    }
+
+    pub fn record_type_parameter_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeTypeParameter)
+    }
+
+    pub fn record_type_node<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeType)
+    }
+
+    pub fn record_macro<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeMacro)
+    }
+
+    pub fn record_union<'a, 'b>(&'a mut self) -> NodeRecorder<'a, 'b> {
+        self.record_node(NodeType::NodeUnion)
+    }
+
+    async fn record_reference(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+        edge_type: EdgeType,
+    ) -> Result<i64, SourcetrailError> {
+        let elem_id = ElementDAO::new(&mut self.executor().await).await?;
+        EdgeDAO::new(
+            &mut self.executor().await,
+            Edge::new(elem_id, edge_type, source_id, target_id),
+        )
+        .await?;
+        Ok(elem_id)
+    }
+
+    /// Records a reference together with its token location
+    /// `(start_line, start_column, end_line, end_column)` in one transaction.
+    pub async fn record_ref_at(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+        edge_type: EdgeType,
+        file_id: i64,
+        (start_line, start_column, end_line, end_column): (i32, i32, i32, i32),
+    ) -> Result<i64, SourcetrailError> {
+        let location = SourceLocation::new(
+            0,
+            file_id,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+            SourceLocationType::Token,
+        )?;
+
+        let mut executor = self.executor().await;
+        let mut tx = executor.begin().await?;
+
+        let elem_id = ElementDAO::new(&mut *tx).await?;
+        EdgeDAO::new(
+            &mut *tx,
+            Edge::new(elem_id, edge_type, source_id, target_id),
+        )
+        .await?;
+        let loc_id = SourceLocationDAO::new(&mut *tx, &location).await?;
+        OccurrenceDAO::new(&mut *tx, Occurrence::new(elem_id, loc_id)).await?;
+
+        tx.commit().await?;
+        Ok(elem_id)
+    }
+
+    /// Records a reference between two symbols given by name. Endpoints not
+    /// yet in the database are recorded as implicit symbols.
+    pub async fn record_ref_by_name(
+        &mut self,
+        source: &NameHierarchy,
+        target: &NameHierarchy,
+        edge: EdgeType,
+    ) -> Result<i64, SourcetrailError> {
+        let source_id = self.resolve_or_record_implicit(source).await?;
+        let target_id = self.resolve_or_record_implicit(target).await?;
+        self.record_reference(source_id, target_id, edge).await
+    }
+
+    async fn resolve_or_record_implicit(
+        &mut self,
+        hierarchy: &NameHierarchy,
+    ) -> Result<i64, SourcetrailError> {
+        if let Some(id) = self.node_id_by_name(hierarchy).await? {
+            return Ok(id);
+        }
+
+        let id = self.record_symbol(hierarchy, NodeType::NodeSymbol).await?;
+        Self::record_symbol_definition_kind(&mut self.executor().await, id, SymbolType::Implicit)
+            .await?;
+        Ok(id)
+    }
+
+    pub fn record_reference_full<'a, 'b>(
+        &'a mut self,
+        source_id: i64,
+        target_id: i64,
+        kind: EdgeType,
+    ) -> ReferenceRecorder<'a, 'b> {
+        ReferenceRecorder::new(self, source_id, target_id, kind)
+    }
+
+    /// Call edges whose target is `target_node_id`.
+    pub async fn find_callers_of(
+        &self,
+        target_node_id: i64,
+    ) -> Result<Vec<Edge>, SourcetrailError> {
+        EdgeDAO::list_by_target(&mut self.executor().await, target_node_id, EdgeType::Call).await
+    }
+
+    /// Call edges whose source is `source_node_id`.
+    pub async fn find_callees_of(
+        &self,
+        source_node_id: i64,
+    ) -> Result<Vec<Edge>, SourcetrailError> {
+        EdgeDAO::list_by_source(&mut self.executor().await, source_node_id, EdgeType::Call).await
+    }
+
+    /// Like [`SourcetrailDB::find_callers_of`], but pairs each call edge with
+    /// its recorded call sites; edges without a location are omitted.
+    pub async fn find_callers_of_with_locations(
+        &self,
+        target_node_id: i64,
+    ) -> Result<Vec<(Edge, SourceLocation)>, SourcetrailError> {
+        EdgeDAO::list_with_locations_by_target(
+            &mut self.executor().await,
+            target_node_id,
+            EdgeType::Call,
+        )
+        .await
+    }
+
+    /// Callers of `callee_id`, ordered by id, each with the locations of its
+    /// call sites.
+    pub async fn get_caller_graph(
+        &self,
+        callee_id: i64,
+    ) -> Result<Vec<(Node, Vec<SourceLocation>)>, SourcetrailError> {
+        self.call_graph(callee_id, true).await
+    }
+
+    /// Callees of `caller_id`, ordered by id, each with the locations of the
+    /// calls made to it.
+    pub async fn get_callee_graph(
+        &self,
+        caller_id: i64,
+    ) -> Result<Vec<(Node, Vec<SourceLocation>)>, SourcetrailError> {
+        self.call_graph(caller_id, false).await
+    }
+
+    // the nodes at the other end of `node_id`'s call edges, grouped with their
+    // call sites; `incoming` selects callers rather than callees
+    async fn call_graph(
+        &self,
+        node_id: i64,
+        incoming: bool,
+    ) -> Result<Vec<(Node, Vec<SourceLocation>)>, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        let edges = if incoming {
+            EdgeDAO::list_by_target(&mut executor, node_id, EdgeType::Call).await?
+        } else {
+            EdgeDAO::list_by_source(&mut executor, node_id, EdgeType::Call).await?
+        };
+
+        let mut call_sites = BTreeMap::<i64, Vec<SourceLocation>>::new();
+        for edge in edges {
+            let locations = SourceLocationDAO::list_by_element(&mut executor, edge.id()).await?;
+            let other_id = if incoming {
+                edge.source_id()
+            } else {
+                edge.target_id()
+            };
+            call_sites.entry(other_id).or_default().extend(locations);
+        }
+
+        let mut nodes = Vec::with_capacity(call_sites.len());
+        for (other_id, locations) in call_sites {
+            let node = NodeDAO::get(&mut executor, other_id)
+                .await?
+                .ok_or(SourcetrailError::NodeNotFound(other_id))?;
+            nodes.push((node, locations));
+        }
+
+        Ok(nodes)
+    }
+
+    /// `(overriding, overridden)` pairs for the members of `class_id` that
+    /// have an `Override` edge.
+    pub async fn list_overriding_methods(
+        &self,
+        class_id: i64,
+    ) -> Result<Vec<(Node, Node)>, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        // members and overrides recorded more than once have one edge each time
+        let mut members = HashSet::new();
+        let mut overrides = Vec::new();
+        for member in EdgeDAO::list_by_source(&mut executor, class_id, EdgeType::Member).await? {
+            let method_id = member.target_id();
+            if !members.insert(method_id) {
+                continue;
+            }
+
+            let mut overridden_ids = HashSet::new();
+            let edges = EdgeDAO::list_by_source(&mut executor, method_id, EdgeType::Override)
+                .await?
+                .into_iter()
+                .filter(|edge| overridden_ids.insert(edge.target_id()))
+                .collect::<Vec<_>>();
+            if edges.is_empty() {
+                continue;
+            }
+
+            let method = NodeDAO::get(&mut executor, method_id)
+                .await?
+                .ok_or(SourcetrailError::NodeNotFound(method_id))?;
+            for edge in edges {
+                let overridden = NodeDAO::get(&mut executor, edge.target_id())
+                    .await?
+                    .ok_or(SourcetrailError::NodeNotFound(edge.target_id()))?;
+                overrides.push((method.clone(), overridden));
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    /// Every inheritance path from `node_id` up to a root type, found
+    /// breadth-first, so shorter paths come first. Each path starts with the
+    /// node itself; a malformed database with an inheritance cycle yields an
+    /// error rather than an endless walk.
+    pub async fn get_inheritance_chain(
+        &self,
+        node_id: i64,
+    ) -> Result<Vec<Vec<Node>>, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        let mut nodes = HashMap::new();
+        let mut chains = Vec::new();
+        let mut queue = VecDeque::from([vec![node_id]]);
+
+        while let Some(path) = queue.pop_front() {
+            let last = *path.last().expect("non-empty path");
+            if let Entry::Vacant(entry) = nodes.entry(last) {
+                let node = NodeDAO::get(&mut executor, last)
+                    .await?
+                    .ok_or(SourcetrailError::NodeNotFound(last))?;
+                entry.insert(node);
+            }
+
+            let bases = EdgeDAO::list_by_source(&mut executor, last, EdgeType::Inheritance).await?;
+            if bases.is_empty() {
+                chains.push(path);
+                continue;
+            }
+
+            for base in bases {
+                let mut next = path.clone();
+                next.push(base.target_id());
+
+                if path.contains(&base.target_id()) {
+                    let cycle = next
+                        .iter()
+                        .map(i64::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    return Err(SourcetrailError::Builder(anyhow::Error::msg(format!(
+                        "inheritance cycle: {cycle}"
+                    ))));
+                }
+
+                queue.push_back(next);
+            }
+        }
+
+        Ok(chains
+            .into_iter()
+            .map(|path| path.iter().map(|id| nodes[id].clone()).collect())
+            .collect())
+    }
+
+    /// The tree of types deriving, directly or indirectly, from `root_id`.
+    pub async fn get_type_hierarchy(
+        &self,
+        root_id: i64,
+    ) -> Result<TypeHierarchyNode, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        let mut nodes = HashMap::new();
+        let mut derived = HashMap::new();
+        let mut queue = VecDeque::from([root_id]);
+
+        while let Some(id) = queue.pop_front() {
+            if derived.contains_key(&id) {
+                continue;
+            }
+
+            let node = NodeDAO::get(&mut executor, id)
+                .await?
+                .ok_or(SourcetrailError::NodeNotFound(id))?;
+            nodes.insert(id, node);
+
+            let children = EdgeDAO::list_by_target(&mut executor, id, EdgeType::Inheritance)
+                .await?
+                .into_iter()
+                .map(|edge| edge.source_id())
+                .collect::<Vec<_>>();
+            queue.extend(children.iter().copied());
+            derived.insert(id, children);
+        }
+
+        TypeHierarchyNode::build(root_id, &nodes, &derived, &mut Vec::new())
+    }
+
+    /// Edges of type `kind`, one entry per recorded location; edges without
+    /// a location appear once, paired with `None`.
+    pub async fn edges_with_locations(
+        &self,
+        kind: EdgeType,
+    ) -> Result<Vec<(Edge, Option<SourceLocation>)>, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        let mut locations = HashMap::<i64, Vec<SourceLocation>>::new();
+        for (edge, location) in EdgeDAO::list_with_locations_by_type(&mut executor, kind).await? {
+            locations.entry(edge.id()).or_default().push(location);
+        }
+
+        let mut edges = Vec::new();
+        for edge in EdgeDAO::list_by_type(&mut executor, kind).await? {
+            match locations.remove(&edge.id()) {
+                Some(sites) => {
+                    edges.extend(sites.into_iter().map(|site| (edge.clone(), Some(site))))
+                }
+                None => edges.push((edge, None)),
+            }
+        }
+
+        Ok(edges)
+    }
+
+    /// Reference occurrences in a file, i.e. locations backed by an edge
+    /// rather than a symbol, ordered by position.
+    pub async fn references_in_file(
+        &self,
+        file_id: i64,
+    ) -> Result<Vec<(Edge, SourceLocation)>, SourcetrailError> {
+        EdgeDAO::list_with_locations_in_file(&mut self.executor().await, file_id).await
+    }
+
+    pub async fn edge_label(&self, edge_id: i64) -> Result<Option<String>, SourcetrailError> {
+        Ok(ElementComponentDAO::get_by_element(
+            &mut self.executor().await,
+            edge_id,
+            ElementComponentType::Label,
+        )
+        .await?
+        .map(|component| component.data().to_owned()))
+    }
+
+    pub async fn components_for(
+        &self,
+        element_id: i64,
+    ) -> Result<Vec<ElementComponent>, SourcetrailError> {
+        ElementComponentDAO::list_for_element(&mut self.executor().await, element_id).await
+    }
+
+    pub async fn record_ref_member(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::Member)
+            .await
+    }
+
+    pub async fn record_implements(
+        &mut self,
+        class_id: i64,
+        interface_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        let interface = NodeDAO::get(&mut self.executor().await, interface_id)
+            .await?
+            .ok_or(SourcetrailError::NodeNotFound(interface_id))?;
+
+        if interface.type_() != NodeType::NodeInterface {
+            return Err(SourcetrailError::UnexpectedNodeType {
+                id: interface_id,
+                expected: NodeType::NodeInterface,
+                found: interface.type_(),
+            });
+        }
+
+        self.record_ref_inheritance(class_id, interface_id).await
+    }
+
+    pub async fn record_ref_type_usage(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::TypeUsage)
+            .await
+    }
+
+    pub async fn record_ref_usage(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::Usage)
+            .await
+    }
+
+    pub async fn record_ref_call(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::Call)
+            .await
+    }
+
+    pub async fn record_ref_inheritance(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::Inheritance)
+            .await
+    }
+
+    pub async fn record_ref_override(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::Override)
+            .await
+    }
+
+    pub async fn record_ref_type_argument(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::TypeArgument)
+            .await
+    }
+
+    pub async fn record_ref_template_specialization(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::TemplateSpecialization)
+            .await
+    }
+
+    pub async fn record_ref_include(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::Include)
+            .await
+    }
+
+    pub async fn record_ref_import(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::Import)
+            .await
+    }
+
+    pub async fn record_ref_bundled_edges(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::BundledEdges)
+            .await
+    }
+
+    pub async fn record_ref_macro_usage(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::MacroUsage)
+            .await
+    }
+
+    pub async fn record_ref_annotation_usage(
+        &mut self,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<i64, SourcetrailError> {
+        self.record_reference(source_id, target_id, EdgeType::AnnotationUsage)
+            .await
+    }
+
+    pub async fn incoming_references_to_file(
+        &self,
+        file_id: i64,
+    ) -> Result<Vec<Edge>, SourcetrailError> {
+        EdgeDAO::list_incoming_to_file(&mut self.executor().await, file_id).await
+    }
+
+    pub fn record_reference_to_unsolved_symbol<'a>(&'a mut self) -> UnsolvedSymbolRecorder<'a> {
+        UnsolvedSymbolRecorder::new(self)
+    }
+
+    /// Returns the id of an unsolved symbol node named `name`, recording it
+    /// on first use, so that many references can target it.
+    pub async fn unsolved_symbol(
+        &mut self,
+        name: impl AsRef<str>,
+    ) -> Result<i64, SourcetrailError> {
+        let hierarchy = NameHierarchy::new(
+            NameHierarchy::NAME_DELIMITER_UNKNOWN,
+            [NameElement::builder().name(name.as_ref()).build()],
+        )?;
+        self.record_symbol(&hierarchy, NodeType::NodeSymbol).await
+    }
+
+    /// Records `(source_id, file_id, start_line, start_column, end_line,
+    /// end_column)` references to the unsolved symbol `unsolved_id` in one
+    /// transaction, returning the reference ids.
+    pub async fn record_unsolved_references(
+        &mut self,
+        unsolved_id: i64,
+        reference_type: EdgeType,
+        references: &[(i64, i64, i32, i32, i32, i32)],
+    ) -> Result<Vec<i64>, SourcetrailError> {
+        let references = references
+            .iter()
+            .map(
+                |&(source_id, file_id, start_line, start_column, end_line, end_column)| {
+                    let location = SourceLocation::new(
+                        0,
+                        file_id,
+                        start_line,
+                        start_column,
+                        end_line,
+                        end_column,
+                        SourceLocationType::Unsolved,
+                    )?;
+                    Ok((source_id, location))
+                },
+            )
+            .collect::<Result<Vec<_>, SourcetrailError>>()?;
+
+        let mut executor = self.executor().await;
+        let mut tx = executor.begin().await?;
+        let mut ids = Vec::with_capacity(references.len());
+
+        for (source_id, location) in &references {
+            let elem_id = ElementDAO::new(&mut *tx).await?;
+            EdgeDAO::new(
+                &mut *tx,
+                Edge::new(elem_id, reference_type, *source_id, unsolved_id),
+            )
+            .await?;
+            let loc_id = SourceLocationDAO::new(&mut *tx, location).await?;
+            OccurrenceDAO::new(&mut *tx, Occurrence::new(elem_id, loc_id)).await?;
+            ids.push(elem_id);
+        }
+
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    pub async fn record_reference_is_ambiguous(
+        &mut self,
+        reference_id: i64,
+    ) -> Result<(), SourcetrailError> {
+        ElementComponentDAO::new(
+            &mut self.executor().await,
+            ElementComponent::new(0, reference_id, ElementComponentType::IsAmbiguous, ""),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Marks `node_id` as deprecated via an `ElementComponentType::Deprecated`
+    /// component. Sourcetrail does not display it; it is only visible to
+    /// tools reading the database through this crate.
+    pub async fn mark_deprecated(&mut self, node_id: i64) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
+        if ElementComponentDAO::get_by_element(
+            &mut executor,
+            node_id,
+            ElementComponentType::Deprecated,
+        )
+        .await?
+        .is_none()
+        {
+            ElementComponentDAO::new(
+                &mut executor,
+                ElementComponent::new(0, node_id, ElementComponentType::Deprecated, ""),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn is_deprecated(&self, node_id: i64) -> Result<bool, SourcetrailError> {
+        Ok(ElementComponentDAO::get_by_element(
+            &mut self.executor().await,
+            node_id,
+            ElementComponentType::Deprecated,
+        )
+        .await?
+        .is_some())
+    }
+
+    pub fn record_file<'a, 'b>(&'a mut self) -> FileRecorder<'a, 'b> {
+        FileRecorder::new(self)
+    }
+
+    async fn record_file_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        modification_time: DateTime<Utc>,
+        content: impl AsRef<str>,
+        indexed: bool,
+        language: impl AsRef<str>,
+    ) -> Result<i64, SourcetrailError> {
+        let path = path.as_ref();
+        let content = content.as_ref();
+
+        let lines = if indexed {
+            content.lines().count() as u32
+        } else {
+            0
+        };
+
+        let elem_id = self.record_file_node(path).await?;
+
+        FileDAO::new(
+            &mut self.executor().await,
+            File::builder()
+                .id(elem_id)
+                .path(path)
+                .modification_time(modification_time)
+                .language(language.as_ref())
+                .indexed(indexed)
+                .complete(true)
+                .line_count(lines)
+                .build()
+                .map_err(SourcetrailError::builder)?,
+        )
+        .await?;
+
+        if indexed {
+            FileContentDAO::new(
+                &mut self.executor().await,
+                FileContent::new(elem_id, content),
+            )
+            .await?;
+        }
+
+        Ok(elem_id)
+    }
+
+    pub async fn record_file_without_content(
+        &mut self,
+        path: impl AsRef<Path>,
+        language: &str,
+    ) -> Result<i64, SourcetrailError> {
+        let path = path.as_ref();
+        let elem_id = self.record_file_node(path).await?;
+
+        FileDAO::new(
+            &mut self.executor().await,
+            File::builder()
+                .id(elem_id)
+                .path(path)
+                .modification_time(chrono::offset::Utc::now())
+                .language(language)
+                .indexed(false)
+                .complete(false)
+                .line_count(0u32)
+                .build()
+                .map_err(SourcetrailError::builder)?,
+        )
+        .await?;
+
+        Ok(elem_id)
+    }
+
+    /// Records every file below `root` whose extension is in `extensions`
+    /// (given with or without the leading dot) in a single transaction, or
+    /// inside the active one. Files and directories that cannot be read are
+    /// skipped and returned with their error; database errors abort the walk.
+    /// Symbolic links to directories are not followed.
+    pub async fn record_directory(
+        &mut self,
+        root: &Path,
+        extensions: &[&str],
+    ) -> Result<(Vec<i64>, Vec<(PathBuf, SourcetrailError)>), SourcetrailError> {
+        let extensions = extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.'))
+            .collect::<Vec<_>>();
+
+        let mut paths = Vec::new();
+        let mut skipped = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    skipped.push((dir, SourcetrailError::file_recorder(e)));
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        skipped.push((dir.clone(), SourcetrailError::file_recorder(e)));
+                        continue;
+                    }
+                };
+                let path = entry.path();
+                let file_type = match entry.file_type() {
+                    Ok(file_type) => file_type,
+                    Err(e) => {
+                        skipped.push((path, SourcetrailError::file_recorder(e)));
+                        continue;
+                    }
+                };
+
+                // symlinked directories are not followed, so links back into
+                // the tree cannot make the walk loop
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else if file_type.is_symlink() && path.is_dir() {
+                    continue;
+                } else if path
+                    .extension()
+                    .is_some_and(|ext| extensions.iter().any(|e| ext == *e))
+                {
+                    paths.push(path);
+                }
+            }
+        }
+
+        paths.sort();
+
+        let owns_transaction = self.transaction.lock().await.is_none();
+        if owns_transaction {
+            self.begin_transaction().await?;
+        }
+
+        let mut ids = Vec::with_capacity(paths.len());
+        let mut result = Ok(());
+        for path in paths {
+            match self.record_file().commit_file(&path).await {
+                Ok(id) => ids.push(id),
+                Err(e @ SourcetrailError::FileRecorder(_)) => skipped.push((path, e)),
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        if owns_transaction {
+            if result.is_ok() {
+                self.commit_transaction().await?;
+            } else {
+                self.rollback_transaction().await?;
+            }
+        }
+
+        result.map(|_| (ids, skipped))
+    }
+
+    async fn record_file_node(&mut self, path: &Path) -> Result<i64, SourcetrailError> {
+        let hierarchy = NameHierarchy::new(
+            NameHierarchy::NAME_DELIMITER_FILE,
+            [NameElement::builder().name(path.to_string_lossy()).build()],
+        )?;
+
+        Self::add_if_not_existing(
+            &mut self.name_cache,
+            &mut Executor::new(&self.database, &self.transaction).await,
+            hierarchy.serialize_name()?,
+            NodeType::NodeFile,
+        )
+        .await
+    }
+
+    pub async fn record_file_language(
+        &mut self,
+        id: i64,
+        language: impl AsRef<str>,
+    ) -> Result<(), SourcetrailError> {
+        let mut file = FileDAO::get(&mut self.executor().await, id)
+            .await?
+            .ok_or(SourcetrailError::FileNotFound(id))?;
+        file.set_language(language.as_ref());
+        FileDAO::update(&mut self.executor().await, file).await?;
+        Ok(())
+    }
+
+    pub async fn list_files_by_language(
+        &self,
+        language: &str,
+    ) -> Result<Vec<File>, SourcetrailError> {
+        FileDAO::list_by_language(&mut self.executor().await, language).await
+    }
+
+    /// Files containing a reference from or to `node_id`, each listed once.
+    pub async fn files_referencing(&self, node_id: i64) -> Result<Vec<File>, SourcetrailError> {
+        FileDAO::list_referencing_node(&mut self.executor().await, node_id).await
+    }
+
+    pub async fn largest_files_by_line_count(&self, n: u64) -> Result<Vec<File>, SourcetrailError> {
+        let limit = i64::try_from(n).unwrap_or(i64::MAX);
+        FileDAO::list_by_line_count(&mut self.executor().await, limit).await
+    }
+
+    /// All recorded errors, each with its `IndexerError` location if it has
+    /// one.
+    pub async fn errors(&self) -> Result<Vec<(Error, Option<SourceLocation>)>, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        let mut locations = HashMap::new();
+        for (error, _, location) in ErrorDAO::list_with_locations(&mut executor).await? {
+            locations.entry(error.id()).or_insert(location);
+        }
+
+        Ok(ErrorDAO::list(&mut executor)
+            .await?
+            .into_iter()
+            .map(|error| {
+                let location = locations.remove(&error.id());
+                (error, location)
+            })
+            .collect())
+    }
+
+    /// Writes one `path:line:column: message` line per recorded error, sorted
+    /// by file and position; fatal errors have their message prefixed with
+    /// `[fatal] `.
+    pub async fn export_errors_text(
+        &self,
+        mut writer: impl std::io::Write,
+    ) -> Result<(), SourcetrailError> {
+        for (error, path, loc) in ErrorDAO::list_with_locations(&mut self.executor().await).await? {
+            writeln!(
+                writer,
+                "{}:{}:{}: {}{}",
+                path,
+                loc.start_line(),
+                loc.start_column(),
+                if error.is_fatal() { "[fatal] " } else { "" },
+                error.message()
+            )?;
+        }
+        Ok(())
+    }
+
+    pub async fn list_files_with_errors(&self) -> Result<Vec<(File, u64)>, SourcetrailError> {
+        Ok(FileDAO::list_with_error_counts(&mut self.executor().await)
+            .await?
+            .into_iter()
+            .map(|(file, count)| (file, count.max(0) as u64))
+            .collect())
+    }
+
+    pub async fn count_files_by_language(&self) -> Result<HashMap<String, u64>, SourcetrailError> {
+        Ok(FileDAO::count_by_language(&mut self.executor().await)
+            .await?
+            .into_iter()
+            .map(|(language, count)| (language, count as u64))
+            .collect())
+    }
+
+    pub async fn set_file_complete(
+        &mut self,
+        id: i64,
+        complete: bool,
+    ) -> Result<(), SourcetrailError> {
+        if FileDAO::get(&mut self.executor().await, id)
+            .await?
+            .is_none()
+        {
+            return Err(SourcetrailError::FileNotFound(id));
+        }
+        FileDAO::set_complete(&mut self.executor().await, id, complete).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn record_source_location(
+        &mut self,
+        symbol_id: i64,
+        file_id: i64,
+        start_line: i32,
+        start_column: i32,
+        end_line: i32,
+        end_column: i32,
+        location_type: SourceLocationType,
+    ) -> Result<(), SourcetrailError> {
+        let location = SourceLocation::new(
+            0,
+            file_id,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+            location_type,
+        )?;
+        self.record_source_locations(&[(symbol_id, location)])
+            .await?;
+        Ok(())
+    }
+
+    /// Records many `(start_line, start_column, end_line, end_column, type)`
+    /// locations of one symbol in one file within a single transaction. Any
+    /// invalid range fails the whole batch before anything is written.
+    pub async fn record_source_locations_batch(
+        &mut self,
+        symbol_id: i64,
+        file_id: i64,
+        ranges: &[(i32, i32, i32, i32, SourceLocationType)],
+    ) -> Result<(), SourcetrailError> {
+        let locations = ranges
+            .iter()
+            .map(
+                |&(start_line, start_column, end_line, end_column, location_type)| {
+                    let location = SourceLocation::new(
+                        0,
+                        file_id,
+                        start_line,
+                        start_column,
+                        end_line,
+                        end_column,
+                        location_type,
+                    )?;
+                    Ok((symbol_id, location))
+                },
+            )
+            .collect::<Result<Vec<_>, SourcetrailError>>()?;
+
+        self.record_source_locations(&locations).await?;
+        Ok(())
+    }
+
+    pub async fn record_source_locations(
+        &mut self,
+        locations: &[(i64, SourceLocation)],
+    ) -> Result<Vec<i64>, SourcetrailError> {
+        let mut executor = self.executor().await;
+        let mut tx = executor.begin().await?;
+        let mut ids = Vec::with_capacity(locations.len());
+
+        for (symbol_id, location) in locations {
+            let loc_id = SourceLocationDAO::new(&mut *tx, location).await?;
+            OccurrenceDAO::new(&mut *tx, Occurrence::new(*symbol_id, loc_id)).await?;
+            ids.push(loc_id);
+        }
+
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    pub async fn find_occurrences_for_symbol(
+        &self,
+        symbol_id: i64,
+    ) -> Result<Vec<SourceLocation>, SourcetrailError> {
+        SourceLocationDAO::list_by_element(&mut self.executor().await, symbol_id).await
+    }
+
+    pub async fn signature_location(
+        &self,
+        symbol_id: i64,
+    ) -> Result<Option<SourceLocation>, SourcetrailError> {
+        SourceLocationDAO::get_by_element(
+            &mut self.executor().await,
+            symbol_id,
+            SourceLocationType::Signature,
+        )
+        .await
+    }
+
+    pub async fn files_of_symbol(&self, node_id: i64) -> Result<Vec<i64>, SourcetrailError> {
+        SourceLocationDAO::list_file_ids_by_element(&mut self.executor().await, node_id).await
+    }
+
+    pub async fn document_symbols(
+        &self,
+        file_id: i64,
+    ) -> Result<Vec<DocumentSymbol>, SourcetrailError> {
+        let mut ranges = BTreeMap::<i64, (Node, Option<Range>, Option<Range>)>::new();
+
+        for (node, loc) in
+            SourceLocationDAO::list_node_locations_in_file(&mut self.executor().await, file_id)
+                .await?
+        {
+            if node.type_() == NodeType::NodeFile {
+                continue;
+            }
+
+            let range = Range::from(&loc);
+            let entry = ranges.entry(node.id()).or_insert((node, None, None));
+            match loc.location_type() {
+                SourceLocationType::Scope => entry.1 = Some(range),
+                SourceLocationType::Token => {
+                    entry.2.get_or_insert(range);
+                }
+                _ => (),
+            }
+        }
+
+        let mut symbols = Vec::with_capacity(ranges.len());
+        for (node, scope, token) in ranges.into_values() {
+            let Some(range) = scope.or(token) else {
+                continue;
+            };
+            let hierarchy = NameHierarchy::deserialize_name(node.name())?;
+            let name = hierarchy
+                .names()
+                .last()
+                .and_then(|elt| elt.name())
+                .unwrap_or_default()
+                .to_owned();
+            symbols.push(DocumentSymbol::new(
+                name,
+                node.type_().into(),
+                range,
+                token.unwrap_or(range),
+            ));
+        }
+
+        Ok(DocumentSymbol::nest(symbols))
+    }
+
+    pub fn record_location<'a>(
+        &'a mut self,
+        kind: SourceLocationType,
+    ) -> SourceLocationRecorder<'a> {
+        SourceLocationRecorder::new(self, kind)
+    }
+
+    pub fn record_symbol_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
+        self.record_location(SourceLocationType::Token)
+    }
+
+    pub fn record_symbol_scope_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
+        self.record_location(SourceLocationType::Scope)
+    }
+
+    pub fn record_symbol_signature_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
+        self.record_location(SourceLocationType::Signature)
+    }
+
+    pub fn record_reference_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
+        self.record_location(SourceLocationType::Token)
+    }
+
+    pub fn record_qualifier_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
+        self.record_location(SourceLocationType::Qualifier)
+    }
+
+    pub async fn record_local_symbol(
+        &mut self,
+        name: impl AsRef<str>,
+    ) -> Result<i64, SourcetrailError> {
+        let (id, _) = self.get_or_create_local_symbol(name.as_ref()).await?;
+        Ok(id)
+    }
+
+    /// Returns the id of the local symbol named `name`, recording it first if
+    /// needed, and whether it was newly created.
+    pub async fn get_or_create_local_symbol(
+        &mut self,
+        name: &str,
+    ) -> Result<(i64, bool), SourcetrailError> {
+        let mut executor = self.executor().await;
+        if let Some(local) = LocalSymbolDAO::get_by_name(&mut executor, name).await? {
+            return Ok((local.id(), false));
+        }
+
+        let mut tx = executor.begin().await?;
+        let elem_id = ElementDAO::new(&mut *tx).await?;
+        LocalSymbolDAO::new(&mut *tx, LocalSymbol::new(elem_id, name)).await?;
+        tx.commit().await?;
+
+        Ok((elem_id, true))
+    }
+
+    /// Renames a local symbol. If another local symbol already has `new_name`
+    /// that symbol's id is returned and `id` is left unchanged.
+    pub async fn rename_local_symbol(
+        &mut self,
+        id: i64,
+        new_name: &str,
+    ) -> Result<i64, SourcetrailError> {
+        let mut executor = self.executor().await;
+
+        let mut local = LocalSymbolDAO::get(&mut executor, id)
+            .await?
+            .ok_or(SourcetrailError::LocalSymbolNotFound(id))?;
+
+        if let Some(existing) = LocalSymbolDAO::get_by_name(&mut executor, new_name).await? {
+            return Ok(existing.id());
+        }
+
+        local.set_name(new_name);
+        LocalSymbolDAO::update(&mut executor, local).await?;
+        Ok(id)
+    }
+
+    pub async fn delete_local_symbol(&mut self, id: i64) -> Result<(), SourcetrailError> {
+        let mut executor = self.executor().await;
+        let mut tx = executor.begin().await?;
+
+        if LocalSymbolDAO::get(&mut *tx, id).await?.is_none() {
+            return Err(SourcetrailError::LocalSymbolNotFound(id));
+        }
+
+        ElementDAO::delete(&mut *tx, id).await?;
+        SourceLocationDAO::delete_unreferenced(&mut *tx).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_local_symbols_in_file(
+        &self,
+        file_id: i64,
+    ) -> Result<Vec<LocalSymbol>, SourcetrailError> {
+        LocalSymbolDAO::list_by_file(&mut self.executor().await, file_id).await
+    }
+
+    pub fn record_local_symbol_location<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
+        self.record_location(SourceLocationType::LocalSymbol)
+    }
+
+    /// Records `(symbol_id, file_id, start_line, start_column, end_line,
+    /// end_column)` local symbol occurrences in one transaction.
+    pub async fn bulk_record_local_symbol_locations(
+        &mut self,
+        locs: &[(i64, i64, i32, i32, i32, i32)],
+    ) -> Result<(), SourcetrailError> {
+        let locations = locs
+            .iter()
+            .map(
+                |&(symbol_id, file_id, start_line, start_column, end_line, end_column)| {
+                    let location = SourceLocation::new(
+                        0,
+                        file_id,
+                        start_line,
+                        start_column,
+                        end_line,
+                        end_column,
+                        SourceLocationType::LocalSymbol,
+                    )?;
+                    Ok((symbol_id, location))
+                },
+            )
+            .collect::<Result<Vec<_>, SourcetrailError>>()?;
+
+        self.record_source_locations(&locations).await?;
+        Ok(())
+    }
+
+    /// Records a range Sourcetrail treats as a single unit, e.g. a multi-line
+    /// string literal or comment: highlighting of other locations is never
+    /// split inside it, and it may span several lines.
+    pub fn record_atomic_source_range<'a>(&'a mut self) -> SourceLocationRecorder<'a> {
+        self.record_location(SourceLocationType::AtomicRange)
+    }
+
+    /// Records an atomic range for a string literal in `file_id`. The range
+    /// is attached to the file itself, as it does not belong to any symbol.
+    pub async fn record_string_literal(
+        &mut self,
+        file_id: i64,
+        (start_line, start_column, end_line, end_column): (i32, i32, i32, i32),
+    ) -> Result<(), SourcetrailError> {
+        self.record_atomic_source_range()
+            .symbol(file_id)
+            .file(file_id)
+            .start_position(start_line, start_column)
+            .end_position(end_line, end_column)
+            .commit()
+            .await
+    }
+
+    pub fn record_error<'a, 'b>(&'a mut self) -> ErrorRecorder<'a, 'b> {
+        ErrorRecorder::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_std::stream::StreamExt;
+    use sqlx::sqlite::SqliteArguments;
+
+    use super::*;
+    use crate::project::SourceGroup;
+
+    #[async_std::test]
+    #[ignore]
+    async fn test_db() -> Result<(), Box<dyn std::error::Error>> {
+        let mut db = SourcetrailDB::create("test").await?;
+
+        let my_main = db.record_class().name("MyMainClass").commit().await?;
+
+        let meth_id = db
+            .record_method()
+            .name("main")
+            .parent(my_main)
+            .commit()
+            .await?;
+
+        let class_id = db.record_class().name("PersonalInfo").commit().await?;
+
+        let field_id = db
+            .record_field()
+            .name("first_name")
+            .parent(class_id)
+            .commit()
+            .await?;
+
+        db.record_ref_usage(meth_id, field_id).await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_eq() {
+        assert_eq!(
+            SourcetrailError::ParentNotFound(5),
+            SourcetrailError::ParentNotFound(5)
+        );
+        assert_ne!(
+            SourcetrailError::ParentNotFound(5),
+            SourcetrailError::ParentNotFound(6)
+        );
+        assert_ne!(
+            SourcetrailError::ParentNotFound(5),
+            SourcetrailError::FileNotFound(5)
+        );
+        assert_eq!(
+            SourcetrailError::InvalidSourceRange,
+            SourcetrailError::InvalidSourceRange
+        );
+        assert_ne!(
+            SourcetrailError::source_location("missing file"),
+            SourcetrailError::source_location("missing file")
+        );
+    }
+
+    async fn temp_db() -> Result<(tempfile::TempDir, SourcetrailDB), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let db = SourcetrailDB::create(dir.path().join("test")).await?;
+        Ok((dir, db))
+    }
+
+    #[async_std::test]
+    async fn test_set_file_complete() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db
+            .record_file()
+            .path("main.c")
+            .content("int main() {}")
+            .commit()
+            .await?;
+
+        let file = FileDAO::get(&db.database, file_id).await?.expect("file");
+        assert!(file.is_complete());
+
+        db.set_file_complete(file_id, false).await?;
+        let file = FileDAO::get(&db.database, file_id).await?.expect("file");
+        assert!(!file.is_complete());
+        assert_eq!(file.line_count(), 1);
+
+        db.set_file_complete(file_id, true).await?;
+        let file = FileDAO::get(&db.database, file_id).await?.expect("file");
+        assert!(file.is_complete());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_incoming_references_to_file() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_a = db
+            .record_file()
+            .path("a.c")
+            .content("void foo() {}")
+            .commit()
+            .await?;
+        let file_b = db
+            .record_file()
+            .path("b.c")
+            .content("void bar() { foo(); }")
+            .commit()
+            .await?;
+
+        let foo = db.record_function().name("foo").commit().await?;
+        db.record_symbol_location()
+            .symbol(foo)
+            .file(file_a)
+            .start_position(1, 6)
+            .end_position(1, 8)
+            .commit()
+            .await?;
+
+        let bar = db.record_function().name("bar").commit().await?;
+        db.record_symbol_location()
+            .symbol(bar)
+            .file(file_b)
+            .start_position(1, 6)
+            .end_position(1, 8)
+            .commit()
+            .await?;
+
+        let call = db.record_ref_call(bar, foo).await?;
+        db.record_reference_location()
+            .symbol(call)
+            .file(file_b)
+            .start_position(1, 14)
+            .end_position(1, 16)
+            .commit()
+            .await?;
+
+        let incoming = db.incoming_references_to_file(file_a).await?;
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].id(), call);
+        assert_eq!(incoming[0].source_id(), bar);
+        assert!(matches!(incoming[0].type_(), EdgeType::Call));
+
+        assert!(db.incoming_references_to_file(file_b).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_source_locations() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db
+            .record_file()
+            .path("main.c")
+            .content("int x;")
+            .commit()
+            .await?;
+        let sym_id = db.record_global_variable().name("x").commit().await?;
+
+        let locations = (1..=10)
+            .map(|line| {
+                SourceLocation::new(0, file_id, line, 1, line, 2, SourceLocationType::Token)
+                    .map(|loc| (sym_id, loc))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let ids = db.record_source_locations(&locations).await?;
+        assert_eq!(ids.len(), 10);
+        assert_eq!(OccurrenceDAO::list(&db.database).await?.len(), 10);
+        assert_eq!(
+            OccurrenceDAO::list_by_element(&db.database, sym_id)
+                .await?
+                .len(),
+            10
+        );
+        assert_eq!(
+            OccurrenceDAO::list_by_location(&db.database, ids[0])
+                .await?
+                .len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_source_location_builder() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let sym_id = db.record_global_variable().name("x").commit().await?;
+
+        let location = SourceLocation::builder()
+            .file_node_id(file_id)
+            .start_line(1)
+            .start_column(5)
+            .end_line(1)
+            .end_column(6)
+            .location_type(SourceLocationType::Token)
+            .build()?;
+        assert_eq!(location.id(), 0);
+
+        let ids = db.record_source_locations(&[(sym_id, location)]).await?;
+        let stored = SourceLocationDAO::get(&db.database, ids[0])
+            .await?
+            .expect("location");
+        assert_eq!(stored.file_node_id(), file_id);
+        assert_eq!(stored.start_column(), 5);
+
+        assert!(SourceLocation::builder()
+            .file_node_id(file_id)
+            .start_line(2)
+            .start_column(1)
+            .end_line(1)
+            .end_column(1)
+            .location_type(SourceLocationType::Token)
+            .build()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_source_location_rejects_negative_positions(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let sym_id = db.record_global_variable().name("x").commit().await?;
+
+        for (start, end) in [
+            ((-2, 1), (1, 4)),
+            ((1, -2), (1, 4)),
+            ((1, 1), (-2, 4)),
+            ((1, 1), (1, -2)),
+            ((0, 1), (1, 4)),
+        ] {
+            let err = db
+                .record_symbol_location()
+                .symbol(sym_id)
+                .file(file_id)
+                .start_position(start.0, start.1)
+                .end_position(end.0, end.1)
+                .commit()
+                .await
+                .expect_err("rejected");
+            assert!(matches!(err, SourcetrailError::SourceLocationBuilder(_)));
+            assert_eq!(
+                err.to_string(),
+                "cannot commit source location record: position must be positive"
+            );
+        }
+
+        assert!(SourceLocationDAO::list(&db.database).await?.is_empty());
+
+        db.record_symbol_location()
+            .symbol(sym_id)
+            .file(file_id)
+            .start_position(1, 0)
+            .end_position(1, 4)
+            .commit()
+            .await?;
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_file_without_content() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db
+            .record_file_without_content("include/shape.h", "cpp")
+            .await?;
+
+        let file = FileDAO::get(&db.database, file_id).await?.expect("file");
+        assert_eq!(file.path(), Path::new("include/shape.h"));
+        assert_eq!(file.language(), "cpp");
+        assert!(!file.is_indexed());
+        assert!(!file.is_complete());
+        assert_eq!(file.line_count(), 0);
+        assert!(FileContentDAO::get(&db.database, file_id).await?.is_none());
+
+        let node = NodeDAO::get(&db.database, file_id).await?.expect("node");
+        assert_eq!(node.type_(), NodeType::NodeFile);
+
+        let stub_id = db
+            .record_file()
+            .path("include/stub.h")
+            .language("cpp")
+            .indexed(false)
+            .commit()
+            .await?;
+
+        let stub = FileDAO::get(&db.database, stub_id).await?.expect("file");
+        assert_eq!(stub.language(), "cpp");
+        assert!(!stub.is_indexed());
+        assert!(FileContentDAO::get(&db.database, stub_id).await?.is_none());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_signature_location() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("shape.cpp").commit().await?;
+        let func_id = db.record_function().name("area").commit().await?;
+
+        assert!(db.signature_location(func_id).await?.is_none());
+
+        db.record_symbol_location()
+            .symbol(func_id)
+            .file(file_id)
+            .start_position(3, 8)
+            .end_position(3, 11)
+            .commit()
+            .await?;
+        db.record_symbol_signature_location()
+            .symbol(func_id)
+            .file(file_id)
+            .start_position(3, 1)
+            .end_position(3, 30)
+            .commit()
+            .await?;
+
+        let signature = db.signature_location(func_id).await?.expect("signature");
+        assert_eq!(signature.location_type(), SourceLocationType::Signature);
+        assert_eq!(signature.file_node_id(), file_id);
+        assert_eq!((signature.start_line(), signature.start_column()), (3, 1));
+        assert_eq!((signature.end_line(), signature.end_column()), (3, 30));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_project_settings() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, db) = temp_db().await?;
+
+        let default_xml = fs::read_to_string(db.project_path())?;
+        assert_eq!(
+            default_xml,
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<config>\n    <version>0</version>\n</config>"
+        );
+
+        let settings = ProjectSettings::new()
+            .source_group(
+                SourceGroup::new("native", SourceGroup::CXX)
+                    .source_path("src")
+                    .source_extension(".cpp")
+                    .exclude_filter("**/third_party/**"),
+            )
+            .source_group(SourceGroup::new("scripts", SourceGroup::PYTHON).source_path("tools"));
+        db.set_project_settings(&settings).await?;
+
+        let xml = fs::read_to_string(db.project_path())?;
+        assert!(xml.contains("<source_group_0>"));
+        assert!(xml.contains("<source_group_1>"));
+        assert!(xml.contains("<type>C++ Source Group</type>"));
+        assert!(xml.contains("<type>Python Source Group</type>"));
+        assert!(xml.contains("<exclude_filter>**/third_party/**</exclude_filter>"));
+
+        let meta = MetaDAO::list(&db.database)
+            .await?
+            .into_iter()
+            .find(|m| m.key() == "project_settings")
+            .expect("project settings");
+        assert_eq!(meta.value(), xml);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_create_with_settings() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+
+        let settings = ProjectSettings::new()
+            .with_name("shapes & more")
+            .with_description("geometry playground")
+            .source_group(SourceGroup::new("native", SourceGroup::CXX).source_path("src"));
+        let db = SourcetrailDB::create_with_settings(dir.path().join("shapes"), settings).await?;
+
+        let xml = fs::read_to_string(db.project_path())?;
+        assert!(xml.contains("<name>shapes &amp; more</name>"));
+        assert!(xml.contains("<description>geometry playground</description>"));
+        assert!(xml.contains("<source_path>src</source_path>"));
+        assert_eq!(
+            db.get_project_settings().await?.as_deref(),
+            Some(xml.as_str())
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_string_literal() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db
+            .record_file()
+            .path("main.c")
+            .content("const char *s = \"a\\\nb\\\nc\";")
+            .commit()
+            .await?;
+        db.record_string_literal(file_id, (1, 17, 3, 2)).await?;
+
+        let ranges = SourceLocationDAO::list_by_element(&db.database, file_id)
+            .await?
+            .into_iter()
+            .filter(|loc| loc.location_type() == SourceLocationType::AtomicRange)
+            .map(|loc| {
+                (
+                    loc.start_line(),
+                    loc.start_column(),
+                    loc.end_line(),
+                    loc.end_column(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, [(1, 17, 3, 2)]);
+
+        assert!(db
+            .record_string_literal(file_id, (3, 2, 1, 17))
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_implicit_node() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let pool = db.database.clone();
+        let definition_kind = |id| {
+            let pool = pool.clone();
+            async move {
+                SymbolDAO::get(&pool, id)
+                    .await
+                    .map(|sym| sym.map(|sym| sym.definition_kind()))
+            }
+        };
+
+        let string = db.record_class().name("String").implicit().commit().await?;
+        assert_eq!(definition_kind(string).await?, Some(SymbolType::Implicit));
+
+        let vector = db
+            .record_class()
+            .name("Vector")
+            .indexed(false)
+            .commit()
+            .await?;
+        assert_eq!(definition_kind(vector).await?, Some(SymbolType::Implicit));
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        assert_eq!(definition_kind(shape).await?, Some(SymbolType::Explicit));
+
+        db.record_class().name("String").commit().await?;
+        db.record_class().name("Shape").implicit().commit().await?;
+        assert_eq!(definition_kind(string).await?, Some(SymbolType::Explicit));
+        assert_eq!(definition_kind(shape).await?, Some(SymbolType::Explicit));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_parent_and_children_of_node() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        let draw = db
+            .record_method()
+            .name("draw")
+            .parent(shape)
+            .commit()
+            .await?;
+
+        assert_eq!(
+            db.get_parent_of_node(area).await?.map(|node| node.id()),
+            Some(shape)
+        );
+        assert!(db.get_parent_of_node(shape).await?.is_none());
+
+        assert_eq!(
+            db.get_children_of_node(shape)
+                .await?
+                .iter()
+                .map(|node| node.id())
+                .collect::<Vec<_>>(),
+            [area, draw]
+        );
+        assert!(db.get_children_of_node(area).await?.is_empty());
+
+        let geo = db.record_namespace().name("geo").commit().await?;
+        let mut circle = 0;
+        let mut radius = 0;
+        for _ in 0..2 {
+            circle = db
+                .record_class()
+                .name("Circle")
+                .parent(geo)
+                .commit()
+                .await?;
+            radius = db
+                .record_method()
+                .name("radius")
+                .parent(circle)
+                .commit()
+                .await?;
+        }
+        assert_eq!(
+            db.get_children_of_node(geo)
+                .await?
+                .iter()
+                .map(|node| node.id())
+                .collect::<Vec<_>>(),
+            [circle]
+        );
+        assert_eq!(
+            db.get_children_of_node(circle)
+                .await?
+                .iter()
+                .map(|node| node.id())
+                .collect::<Vec<_>>(),
+            [radius]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_node_with_path() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let id = db
+            .record_node_with_path(".", "com.example.Shape", NodeType::NodeClass)
+            .await?;
+        assert_eq!(db.get_qualified_name(id).await?, "com.example.Shape");
+        assert_eq!(
+            NodeDAO::get(&db.database, id)
+                .await?
+                .map(|node| node.type_()),
+            Some(NodeType::NodeClass)
+        );
+
+        let parent = db.get_parent_of_node(id).await?.expect("parent");
+        assert_eq!(db.get_qualified_name(parent.id()).await?, "com.example");
+        assert_eq!(parent.type_(), NodeType::NodeSymbol);
+
+        assert!(matches!(
+            db.record_node_with_path(".", "com..Shape", NodeType::NodeClass)
+                .await,
+            Err(SourcetrailError::InvalidQualifiedName(_))
+        ));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_resolve_names() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+
+        let names = ["Shape::area", "Circle", "Shape", "Shape::draw"]
+            .into_iter()
+            .map(NameHierarchy::from_cxx_qualified_name)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            db.resolve_names(&names).await?,
+            [Some(area), None, Some(shape), None]
+        );
+
+        let many = (0..SqliteHelper::MAX_VARIABLES + 1)
+            .map(|_| names[2].clone())
+            .collect::<Vec<_>>();
+        let ids = db.resolve_names(&many).await?;
+        assert_eq!(ids.len(), many.len());
+        assert!(ids.iter().all(|id| *id == Some(shape)));
+
+        assert!(db.resolve_names(&[]).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_existing_node_ids_for() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+
+        let names = ["Shape::area", "Shape::draw", "Shape", "Circle"].map(String::from);
+        let ids = db
+            .existing_node_ids_for(&names, NameHierarchy::NAME_DELIMITER_CXX)
+            .await?;
+        assert_eq!(
+            ids,
+            HashMap::from([
+                ("Shape::area".to_owned(), area),
+                ("Shape".to_owned(), shape)
+            ])
+        );
+
+        // the delimiter is part of a node's identity
+        assert!(db
+            .existing_node_ids_for(&names, NameHierarchy::NAME_DELIMITER_JAVA)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_stats() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        assert_eq!(db.stats().await?, DbStats::default());
+
+        db.record_file().path("main.c").commit().await?;
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        let main = db.record_function().name("main").commit().await?;
+        db.record_ref_call(main, area).await?;
+
+        let stats = db.stats().await?;
+        assert_eq!(stats.nodes(), 4);
+        assert_eq!(stats.edges(), 2);
+        assert_eq!(stats.files(), 1);
+        assert_eq!(stats.symbols(), 3);
+        assert_eq!(stats.nodes_of_type(NodeType::NodeClass), 1);
+        assert_eq!(stats.nodes_of_type(NodeType::NodeFile), 1);
+        assert_eq!(stats.nodes_of_type(NodeType::NodeNamespace), 0);
+        assert_eq!(stats.nodes_by_type().values().sum::<u64>(), stats.nodes());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_mark_deprecated() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+
+        db.mark_deprecated(area).await?;
+        db.mark_deprecated(area).await?;
+
+        assert!(db.is_deprecated(area).await?);
+        assert!(!db.is_deprecated(shape).await?);
+        assert_eq!(
+            db.components_for(area)
+                .await?
+                .iter()
+                .map(|component| component.component_type())
+                .collect::<Vec<_>>(),
+            [ElementComponentType::Deprecated]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_deprecated_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test");
+
+        let mut db = SourcetrailDB::create(&path).await?;
+        let legacy = db.record_function().name("legacy_area").commit().await?;
+        let area = db.record_function().name("area").commit().await?;
+        db.mark_deprecated(legacy).await?;
+        db.close().await?;
+
+        let db = SourcetrailDB::open(&path, false).await?;
+        assert!(db.is_deprecated(legacy).await?);
+        assert!(!db.is_deprecated(area).await?);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_find_occurrences_for_symbol() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db
+            .record_file()
+            .path("main.c")
+            .content("int x;\nx = 1;\nx = 2;")
+            .commit()
+            .await?;
+        let sym_id = db.record_global_variable().name("x").commit().await?;
+
+        for line in 1..=3 {
+            db.record_symbol_location()
+                .symbol(sym_id)
+                .file(file_id)
+                .start_position(line, 1)
+                .end_position(line, 2)
+                .commit()
+                .await?;
+        }
+
+        let mut lines = db
+            .find_occurrences_for_symbol(sym_id)
+            .await?
+            .iter()
+            .map(|loc| {
+                assert_eq!(loc.file_node_id(), file_id);
+                loc.start_line()
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(lines, [1, 2, 3]);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_open_with_options() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test");
+
+        let mut db =
+            SourcetrailDB::open_with_options(&path, true, OpenOptions::new().max_connections(1))
+                .await?;
+
+        let class_id = db.record_class().name("PersonalInfo").commit().await?;
+        let field_id = db
+            .record_field()
+            .name("first_name")
+            .parent(class_id)
+            .commit()
+            .await?;
+        let file_id = db
+            .record_file()
+            .path("info.java")
+            .content("")
+            .commit()
+            .await?;
+        db.record_symbol_location()
+            .symbol(field_id)
+            .file(file_id)
+            .start_position(1, 1)
+            .end_position(1, 10)
+            .commit()
+            .await?;
+        db.close().await?;
+
+        assert!(
+            SourcetrailDB::create_with_options(&path, OpenOptions::new().max_connections(2))
+                .await
+                .is_err()
+        );
+
+        let db = SourcetrailDB::open_with_options(
+            &path,
+            false,
+            OpenOptions::new()
+                .max_connections(1)
+                .journal_mode(JournalMode::Delete)
+                .synchronous(Synchronous::Full),
+        )
+        .await?;
+        assert_eq!(NodeDAO::list(&db.database).await?.len(), 3);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_connection_pragmas() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+
+        let db = SourcetrailDB::create_with_options(
+            dir.path().join("test"),
+            OpenOptions::new()
+                .max_connections(3)
+                .busy_timeout(Duration::from_millis(1234)),
+        )
+        .await?;
+
+        // hold every connection at once so each one is checked
+        let mut connections = Vec::new();
+        for _ in 0..3 {
+            connections.push(db.database.acquire().await?);
+        }
+
+        for conn in &mut connections {
+            let foreign_keys = sqlx::query_scalar::<_, i64>("PRAGMA foreign_keys;")
+                .fetch_one(&mut **conn)
+                .await?;
+            let busy_timeout = sqlx::query_scalar::<_, i64>("PRAGMA busy_timeout;")
+                .fetch_one(&mut **conn)
+                .await?;
+            assert_eq!((foreign_keys, busy_timeout), (1, 1234));
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_open_wal() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test");
+
+        let mut db = SourcetrailDB::create(&path).await?;
+        db.record_class().name("Shape").commit().await?;
+        db.close().await?;
+
+        let mut db = SourcetrailDB::open_with_options(
+            &path,
+            false,
+            OpenOptions::new().journal_mode(JournalMode::Wal),
+        )
+        .await?;
+
+        let (mode,) = SqliteHelper::fetch_one::<(String,)>(
+            &db.database,
+            "PRAGMA journal_mode;",
+            SqliteArguments::default(),
+        )
+        .await?
+        .expect("journal mode");
+        assert_eq!(mode, "wal");
+
+        // readers on other connections see the last committed state while a
+        // write transaction is open
+        let mut tx = db.database.begin().await?;
+        let elem_id = ElementDAO::new(&mut *tx).await?;
+        NodeDAO::new(&mut *tx, Node::new(elem_id, NodeType::NodeClass, "Circle")).await?;
+        assert_eq!(db.node_count().await?, 1);
+        tx.commit().await?;
+        assert_eq!(db.node_count().await?, 2);
+
+        db.record_class().name("Square").commit().await?;
+        db.close().await?;
+
+        let db = SourcetrailDB::open(&path, false).await?;
+        assert_eq!(db.node_count().await?, 3);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_node_id_by_name() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test");
+
+        let mut db = SourcetrailDB::create(&path).await?;
+        let shape = db.record_class().name("Shape").commit().await?;
+        db.close().await?;
+
+        let mut db = SourcetrailDB::open(&path, false).await?;
+        let hierarchy = NameHierarchy::new(
+            NameHierarchy::NAME_DELIMITER_CXX,
+            [NameElement::builder().name("Shape").build()],
+        )?;
+        assert_eq!(db.node_id_by_name(&hierarchy).await?, Some(shape));
+        assert_eq!(
+            db.name_cache.get(&hierarchy.serialize_name()?),
+            Some(&shape)
+        );
+
+        let missing = NameHierarchy::new(
+            NameHierarchy::NAME_DELIMITER_CXX,
+            [NameElement::builder().name("Circle").build()],
+        )?;
+        assert_eq!(db.node_id_by_name(&missing).await?, None);
+
+        let caller = db.record_function().name("main").commit().await?;
+        db.record_ref_type_usage(caller, shape).await?;
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_delete_local_symbol() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let local = db.record_local_symbol("main.c<3:9>").await?;
+        db.record_local_symbol_location()
+            .symbol(local)
+            .file(file_id)
+            .start_position(3, 9)
+            .end_position(3, 12)
+            .commit()
+            .await?;
+        assert_eq!(db.list_local_symbols_in_file(file_id).await?.len(), 1);
+
+        db.delete_local_symbol(local).await?;
+
+        assert!(db.list_local_symbols_in_file(file_id).await?.is_empty());
+        assert!(LocalSymbolDAO::get(&db.database, local).await?.is_none());
+        assert!(ElementDAO::get(&db.database, local).await?.is_none());
+        assert!(SourceLocationDAO::list(&db.database).await?.is_empty());
+
+        assert_eq!(
+            db.delete_local_symbol(local).await,
+            Err(SourcetrailError::LocalSymbolNotFound(local))
+        );
+        let main = db.record_function().name("main").commit().await?;
+        assert_eq!(
+            db.delete_local_symbol(main).await,
+            Err(SourcetrailError::LocalSymbolNotFound(main))
+        );
+        assert!(NodeDAO::get(&db.database, main).await?.is_some());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_or_create_local_symbol() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let (id, created) = db.get_or_create_local_symbol("main.c<3:9>").await?;
+        assert!(created);
+        assert_eq!(
+            db.get_or_create_local_symbol("main.c<3:9>").await?,
+            (id, false)
+        );
+        assert_eq!(db.record_local_symbol("main.c<3:9>").await?, id);
+
+        let (other, created) = db.get_or_create_local_symbol("main.c<4:9>").await?;
+        assert!(created);
+        assert_ne!(other, id);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_bulk_record_local_symbol_locations() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let i = db.record_local_symbol("main.c<3:9>").await?;
+        let j = db.record_local_symbol("main.c<4:9>").await?;
+
+        db.bulk_record_local_symbol_locations(&[
+            (i, file_id, 3, 9, 3, 10),
+            (i, file_id, 5, 12, 5, 13),
+            (j, file_id, 4, 9, 4, 10),
+        ])
+        .await?;
+
+        assert_eq!(
+            OccurrenceDAO::list_by_element(&db.database, i).await?.len(),
+            2
+        );
+        assert_eq!(db.list_local_symbols_in_file(file_id).await?.len(), 2);
+        assert!(SourceLocationDAO::list(&db.database)
+            .await?
+            .iter()
+            .all(|loc| loc.location_type() == SourceLocationType::LocalSymbol));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_rename_local_symbol() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let first = db.record_local_symbol("main.c<3:9>").await?;
+        let second = db.record_local_symbol("main.c<4:9>").await?;
+
+        assert_eq!(db.rename_local_symbol(first, "main.c<3:5>").await?, first);
+        assert_eq!(db.record_local_symbol("main.c<3:5>").await?, first);
+
+        assert_eq!(db.rename_local_symbol(second, "main.c<3:5>").await?, first);
+        let unchanged = LocalSymbolDAO::get(&db.database, second)
+            .await?
+            .expect("local symbol");
+        assert_eq!(unchanged.name(), "main.c<4:9>");
+
+        assert_eq!(
+            db.rename_local_symbol(second + 1, "main.c<5:1>").await,
+            Err(SourcetrailError::LocalSymbolNotFound(second + 1))
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_transaction() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test");
+        let mut db = SourcetrailDB::create(&path).await?;
+
+        assert_eq!(
+            db.commit_transaction().await,
+            Err(SourcetrailError::NoTransaction)
+        );
+        assert_eq!(
+            db.rollback_transaction().await,
+            Err(SourcetrailError::NoTransaction)
+        );
+
+        let shape = db.record_class().name("Shape").commit().await?;
+
+        db.begin_transaction().await?;
+        assert_eq!(
+            db.begin_transaction().await,
+            Err(SourcetrailError::TransactionActive)
+        );
+        db.record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        db.record_class().name("Circle").commit().await?;
+        assert_eq!(db.node_count().await?, 3);
+        db.rollback_transaction().await?;
+
+        assert_eq!(db.node_count().await?, 1);
+        assert_eq!(db.edge_count().await?, 0);
+
+        // ids are reused once the highest element is deleted, so a rollback
+        // must restore the cached names rather than keep older ids
+        let square = db.record_class().name("Square").commit().await?;
+        db.begin_transaction().await?;
+        db.delete_node(square).await?;
+        let triangle = db.record_class().name("Triangle").commit().await?;
+        assert_eq!(triangle, square);
+        db.rollback_transaction().await?;
+
+        assert_eq!(db.record_class().name("Square").commit().await?, square);
+        let triangle = db.record_class().name("Triangle").commit().await?;
+        assert_ne!(triangle, square);
+        assert_eq!(db.node_count().await?, 3);
+        db.delete_node(triangle).await?;
+        db.delete_node(square).await?;
+
+        db.begin_transaction().await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+        db.record_ref_inheritance(circle, shape).await?;
+        let file_id = db.record_file().path("shape.cpp").commit().await?;
+        db.record_symbol_location()
+            .symbol(circle)
+            .file(file_id)
+            .start_position(1, 7)
+            .end_position(1, 12)
+            .commit()
+            .await?;
+        db.commit_transaction().await?;
+        db.close().await?;
+
+        let db = SourcetrailDB::open(&path, false).await?;
+        assert_eq!(db.node_count().await?, 3);
+        assert_eq!(db.edge_count().await?, 1);
+        assert_eq!(db.files_of_symbol(circle).await?, [file_id]);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_transaction_guard() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        {
+            let mut tx = db.transaction().await?;
+            let shape = tx.record_class().name("Shape").commit().await?;
+            tx.record_method()
+                .name("area")
+                .parent(shape)
+                .commit()
+                .await?;
+            assert!(matches!(
+                tx.begin_transaction().await,
+                Err(SourcetrailError::TransactionActive)
+            ));
+        }
+        assert_eq!(db.node_count().await?, 0);
+
+        let mut tx = db.transaction().await?;
+        let file_id = tx.record_file().path("shape.h").commit().await?;
+        let shape = tx.record_class().name("Shape").commit().await?;
+        tx.record_symbol_location()
+            .symbol(shape)
+            .file(file_id)
+            .start_position(1, 7)
+            .end_position(1, 12)
+            .commit()
+            .await?;
+        tx.commit().await?;
+
+        assert_eq!(db.node_count().await?, 2);
+        assert_eq!(db.files_of_symbol(shape).await?.len(), 1);
+
+        let tx = db.transaction().await?;
+        tx.rollback().await?;
+        assert!(matches!(
+            db.commit_transaction().await,
+            Err(SourcetrailError::NoTransaction)
+        ));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_count_files_by_language() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        for (path, language) in [("a.c", "c"), ("b.c", "c"), ("c.py", "python")] {
+            let id = db.record_file().path(path).commit().await?;
+            db.record_file_language(id, language).await?;
+        }
+
+        let counts = db.count_files_by_language().await?;
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["c"], 2);
+        assert_eq!(counts["python"], 1);
+
+        let python = db.list_files_by_language("python").await?;
+        assert_eq!(python.len(), 1);
+        assert_eq!(python[0].path(), Path::new("c.py"));
+        assert!(db.list_files_by_language("java").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_delete_node() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db
+            .record_file()
+            .path("main.c")
+            .content("void foo() {}\nvoid bar() { foo(); }")
+            .commit()
+            .await?;
+
+        let foo = db.record_function().name("foo").commit().await?;
+        db.record_symbol_location()
+            .symbol(foo)
+            .file(file_id)
+            .start_position(1, 6)
+            .end_position(1, 8)
+            .commit()
+            .await?;
+
+        let bar = db.record_function().name("bar").commit().await?;
+        db.record_symbol_location()
+            .symbol(bar)
+            .file(file_id)
+            .start_position(2, 6)
+            .end_position(2, 8)
+            .commit()
+            .await?;
+
+        let call = db.record_ref_call(bar, foo).await?;
+        db.record_reference_location()
+            .symbol(call)
+            .file(file_id)
+            .start_position(2, 14)
+            .end_position(2, 16)
+            .commit()
+            .await?;
+
+        db.delete_node(foo).await?;
+
+        assert!(NodeDAO::get(&db.database, foo).await?.is_none());
+        assert!(SymbolDAO::get(&db.database, foo).await?.is_none());
+        assert!(ElementDAO::get(&db.database, foo).await?.is_none());
+        assert!(ElementDAO::get(&db.database, call).await?.is_none());
+        assert!(EdgeDAO::list(&db.database).await?.is_empty());
+
+        let occurrences = OccurrenceDAO::list(&db.database).await?;
+        assert_eq!(occurrences.len(), 1);
+        assert_eq!(occurrences[0].element_id(), bar);
+        assert_eq!(SourceLocationDAO::list(&db.database).await?.len(), 1);
+
+        let foo_again = db.record_function().name("foo").commit().await?;
+        assert_ne!(foo_again, foo);
+        assert!(NodeDAO::get(&db.database, foo_again).await?.is_some());
+
+        assert_eq!(
+            db.delete_node(foo).await,
+            Err(SourcetrailError::NodeNotFound(foo))
+        );
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        let nodes = db.node_count().await?;
+        db.delete_node(shape).await?;
+        assert!(NodeDAO::get(&db.database, area).await?.is_none());
+        assert_eq!(db.node_count().await?, nodes - 2);
+
+        // the deleted members must not linger in the name cache
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        assert!(NodeDAO::get(&db.database, area).await?.is_some());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_delete_file() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db
+            .record_file()
+            .path("main.c")
+            .content("int x;")
+            .commit()
+            .await?;
+        let sym_id = db.record_global_variable().name("x").commit().await?;
+        db.record_symbol_location()
+            .symbol(sym_id)
+            .file(file_id)
+            .start_position(1, 5)
+            .end_position(1, 6)
+            .commit()
+            .await?;
+
+        db.delete_file(file_id).await?;
+
+        assert!(FileDAO::get(&db.database, file_id).await?.is_none());
+        assert!(FileContentDAO::get(&db.database, file_id).await?.is_none());
+        assert!(NodeDAO::get(&db.database, file_id).await?.is_none());
+        assert!(SourceLocationDAO::list(&db.database).await?.is_empty());
+        assert!(OccurrenceDAO::list(&db.database).await?.is_empty());
+        assert!(NodeDAO::get(&db.database, sym_id).await?.is_some());
+
+        assert_eq!(
+            db.delete_file(file_id).await,
+            Err(SourcetrailError::FileNotFound(file_id))
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_checkpoint() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut db = SourcetrailDB::create_with_options(
+            dir.path().join("test"),
+            OpenOptions::new().journal_mode(JournalMode::Wal),
+        )
+        .await?;
+
+        db.record_class().name("MyMainClass").commit().await?;
+
+        let wal = dir.path().join("test.srctrldb-wal");
+        assert!(fs::metadata(&wal)?.len() > 0);
+
+        db.checkpoint().await?;
+        assert_eq!(fs::metadata(&wal)?.len(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_optimize() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let class_id = db.record_class().name("Shape").commit().await?;
+        for i in 0..100 {
+            db.record_method()
+                .name(format!("m{i}"))
+                .parent(class_id)
+                .commit()
+                .await?;
+        }
+        db.delete_node(class_id).await?;
+
+        let nodes = db.node_count().await?;
+        let edges = db.edge_count().await?;
+
+        db.optimize().await?;
+        db.defragment().await?;
+
+        assert_eq!(db.node_count().await?, nodes);
+        assert_eq!(db.edge_count().await?, edges);
+        assert_eq!(
+            SqliteHelper::count(
+                &db.database,
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'sqlite_stat1';",
+                SqliteArguments::default(),
+            )
+            .await?,
+            1
+        );
+
+        db.begin_transaction().await?;
+        assert_eq!(db.vacuum().await, Err(SourcetrailError::TransactionActive));
+        db.rollback_transaction().await?;
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_error_id() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let error_id = db
+            .record_error()
+            .message("unknown type name 'Shape'")
+            .fatal(true)
+            .file(file_id)
+            .start_position(3, 1)
+            .end_position(3, 6)
+            .commit()
+            .await?;
+
+        let error = ErrorDAO::get(&db.database, error_id).await?.expect("error");
+        assert_eq!(error.message(), "unknown type name 'Shape'");
+        assert!(error.is_fatal());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let fatal = db
+            .record_error()
+            .message("'shape.h' file not found")
+            .fatal(true)
+            .file(file_id)
+            .start_position(1, 10)
+            .end_position(1, 19)
+            .commit()
+            .await?;
+        let unlocated = ElementDAO::new(&db.database).await?;
+        ErrorDAO::new(
+            &db.database,
+            Error::new(unlocated, "too many errors emitted", false, true, ""),
+        )
+        .await?;
+
+        let errors = db.errors().await?;
+        assert_eq!(errors.len(), 2);
+
+        let (error, location) = &errors[0];
+        assert_eq!(error.id(), fatal);
+        assert_eq!(error.message(), "'shape.h' file not found");
+        assert!(error.is_fatal());
+        assert_eq!(location.as_ref().map(|loc| loc.start_line()), Some(1));
+
+        assert_eq!(errors[1].0.id(), unlocated);
+        assert!(errors[1].1.is_none());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_list_files_with_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let clean = db.record_file().path("clean.c").commit().await?;
+        let broken = db.record_file().path("broken.c").commit().await?;
+        let worse = db.record_file().path("worse.c").commit().await?;
+
+        for (file_id, line) in [(broken, 1), (worse, 2), (worse, 7), (broken, 4), (worse, 9)] {
+            db.record_error()
+                .message("expected ';'")
+                .file(file_id)
+                .start_position(line, 1)
+                .end_position(line, 2)
+                .commit()
+                .await?;
+        }
+
+        let files = db.list_files_with_errors().await?;
+        let counts = files
+            .iter()
+            .map(|(file, count)| (file.id(), *count))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [(broken, 2), (worse, 3)]);
+        assert!(files.iter().all(|(file, _)| file.id() != clean));
+        assert_eq!(files[0].0.path(), Path::new("broken.c"));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_backup() -> Result<(), Box<dyn std::error::Error>> {
+        let (dir, mut db) = temp_db().await?;
+
+        db.record_class().name("Shape").commit().await?;
+
+        let dest = dir.path().join("backup");
+        db.backup(&dest, false).await?;
+        assert!(dir.path().join("backup.srctrlprj").exists());
+        assert_eq!(
+            db.backup(&dest, false).await,
+            Err(SourcetrailError::File(format!(
+                "{} already exists",
+                dir.path().join("backup.srctrldb").display()
+            )))
+        );
+
+        db.record_class().name("Circle").commit().await?;
+        db.backup(&dest, true).await?;
+        assert!(!dir.path().join("backup.srctrldb.tmp").exists());
+
+        let copy = SourcetrailDB::open(&dest, false).await?;
+        assert_eq!(copy.node_count().await?, 2);
+        assert_eq!(copy.get_storage_version().await?, Some(25));
+
+        // only the project file exists at the destination
+        let other = dir.path().join("other");
+        fs::write(dir.path().join("other.srctrlprj"), "keep")?;
+        assert_eq!(
+            db.backup(&other, false).await,
+            Err(SourcetrailError::File(format!(
+                "{} already exists",
+                dir.path().join("other.srctrlprj").display()
+            )))
+        );
+        assert!(!dir.path().join("other.srctrldb").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("other.srctrlprj"))?,
+            "keep"
+        );
+
+        let db_path = db.path.clone();
+        assert!(matches!(
+            db.backup(&db_path, true).await,
+            Err(SourcetrailError::File(_))
+        ));
+        db.record_class().name("Square").commit().await?;
+        assert_eq!(db.node_count().await?, 3);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_export_errors_text() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let main = db.record_file().path("src/main.c").commit().await?;
+        let util = db.record_file().path("src/util.c").commit().await?;
+
+        for (file_id, line, fatal, msg) in [
+            (main, 12, false, "unknown type name 'foo'"),
+            (util, 3, true, "'util.h' file not found"),
+            (main, 4, false, "expected ';'"),
+        ] {
+            db.record_error()
+                .message(msg)
+                .fatal(fatal)
+                .file(file_id)
+                .start_position(line, 5)
+                .end_position(line, 9)
+                .commit()
+                .await?;
+        }
+
+        let mut out = Vec::new();
+        db.export_errors_text(&mut out).await?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            "src/main.c:4:5: expected ';'\n\
+             src/main.c:12:5: unknown type name 'foo'\n\
+             src/util.c:3:5: [fatal] 'util.h' file not found\n"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_implements() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let iface = db.record_interface().name("Runnable").commit().await?;
+        let class = db.record_class().name("Worker").commit().await?;
+
+        let edge_id = db.record_implements(class, iface).await?;
+        let edge = EdgeDAO::get(&db.database, edge_id).await?.expect("edge");
+        assert_eq!(edge.type_(), EdgeType::Inheritance);
+        assert_eq!(edge.source_id(), class);
+        assert_eq!(edge.target_id(), iface);
+
+        let other = db.record_class().name("Base").commit().await?;
+        assert_eq!(
+            db.record_implements(class, other).await,
+            Err(SourcetrailError::UnexpectedNodeType {
+                id: other,
+                expected: NodeType::NodeInterface,
+                found: NodeType::NodeClass,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_bulk_write_pragmas() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let db =
+            SourcetrailDB::create_with_options(dir.path().join("test"), OpenOptions::bulk_write())
+                .await?;
+
+        let (mode,) = SqliteHelper::fetch_one::<(String,)>(
+            &db.database,
+            "PRAGMA journal_mode;",
+            SqliteArguments::default(),
+        )
+        .await?
+        .expect("journal mode");
+        assert_eq!(mode, "wal");
+
+        let (synchronous,) = SqliteHelper::fetch_one::<(i64,)>(
+            &db.database,
+            "PRAGMA synchronous;",
+            SqliteArguments::default(),
+        )
+        .await?
+        .expect("synchronous");
+        assert_eq!(synchronous, 1);
+
+        let (timeout,) = SqliteHelper::fetch_one::<(i64,)>(
+            &db.database,
+            "PRAGMA busy_timeout;",
+            SqliteArguments::default(),
+        )
+        .await?
+        .expect("busy timeout");
+        assert_eq!(timeout, 30_000);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_child_method_with_access() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let class_id = db.record_class().name("Account").commit().await?;
+        let meth_id = db
+            .record_child_method_with_access(class_id, ComponentAccessType::Private)
+            .name("audit")
+            .commit()
+            .await?;
+
+        let edges = EdgeDAO::list(&db.database).await?;
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].type_(), EdgeType::Member);
+        assert_eq!(edges[0].source_id(), class_id);
+        assert_eq!(edges[0].target_id(), meth_id);
+
+        let access = ComponentAccessDAO::get(&db.database, meth_id)
+            .await?
+            .expect("access");
+        assert_eq!(access.access_type(), ComponentAccessType::Private);
+        assert!(ComponentAccessDAO::get(&db.database, class_id)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_node_access() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let class_id = db.record_class().name("Account").commit().await?;
         let field_id = db
             .record_field()
-            .name("first_name")
+            .name("balance")
+            .parent(class_id)
+            .commit()
+            .await?;
+        let meth_id = db
+            .record_method()
+            .name("audit")
+            .parent(class_id)
+            .access(ComponentAccessType::Private)
+            .commit()
+            .await?;
+
+        let access = ComponentAccessDAO::get(&db.database, meth_id)
+            .await?
+            .expect("access");
+        assert_eq!(access.access_type(), ComponentAccessType::Private);
+        assert!(ComponentAccessDAO::get(&db.database, field_id)
+            .await?
+            .is_none());
+
+        let owner_id = db
+            .record_field()
+            .name("owner")
+            .parent(class_id)
+            .access_type(ComponentAccessType::Protected)
+            .commit()
+            .await?;
+        let id_id = db
+            .record_field()
+            .name("id")
+            .parent(class_id)
+            .access_type(ComponentAccessType::None)
+            .commit()
+            .await?;
+        assert_eq!(
+            ComponentAccessDAO::get(&db.database, owner_id)
+                .await?
+                .map(|access| access.access_type()),
+            Some(ComponentAccessType::Protected)
+        );
+        assert!(ComponentAccessDAO::get(&db.database, id_id)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_edge_label() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let caller = db.record_function().name("main").commit().await?;
+        let callee = db.record_function().name("area").commit().await?;
+        let shape = db.record_class().name("Shape").commit().await?;
+
+        let labelled = db
+            .record_reference_full(callee, shape, EdgeType::TypeUsage)
+            .label("argument")
+            .commit()
+            .await?;
+        let plain = db
+            .record_reference_full(caller, callee, EdgeType::Call)
+            .commit()
+            .await?;
+
+        assert_eq!(db.edge_label(labelled).await?.as_deref(), Some("argument"));
+        assert_eq!(db.edge_label(plain).await?, None);
+
+        let edge = EdgeDAO::get(&db.database, labelled).await?.expect("edge");
+        assert_eq!(edge.type_(), EdgeType::TypeUsage);
+        assert_eq!(edge.source_id(), callee);
+        assert_eq!(edge.target_id(), shape);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_default_delimiter() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+        assert_eq!(db.default_delimiter(), NameHierarchy::NAME_DELIMITER_CXX);
+
+        let shape = db.record_class().name("Shape").commit().await?;
+
+        db.set_default_delimiter(NameHierarchy::NAME_DELIMITER_JAVA);
+        let list = db.record_class().name("List").commit().await?;
+        let vector = db
+            .record_class()
+            .name("Vector")
+            .delimiter(NameHierarchy::NAME_DELIMITER_CXX)
+            .commit()
+            .await?;
+
+        for (id, delimiter) in [
+            (shape, NameHierarchy::NAME_DELIMITER_CXX),
+            (list, NameHierarchy::NAME_DELIMITER_JAVA),
+            (vector, NameHierarchy::NAME_DELIMITER_CXX),
+        ] {
+            let node = NodeDAO::get(&db.database, id).await?.expect("node");
+            assert!(node.name().starts_with(&format!("{delimiter}\tm")));
+            let hierarchy = NameHierarchy::deserialize_name(node.name())?;
+            assert_eq!(hierarchy.delimiter(), delimiter);
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_strict_parent() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let width = db
+            .record_field()
+            .name("width")
+            .parent(shape)
+            .strict_parent(true)
+            .commit()
+            .await?;
+
+        assert_eq!(
+            db.record_field()
+                .name("unit")
+                .parent(width)
+                .strict_parent(true)
+                .commit()
+                .await,
+            Err(SourcetrailError::InvalidParent {
+                id: width,
+                parent: NodeType::NodeField,
+                child: NodeType::NodeField,
+            })
+        );
+
+        // lax by default
+        db.record_field()
+            .name("unit")
+            .parent(width)
+            .commit()
+            .await?;
+
+        assert!(NodeType::NodeNamespace.can_contain(NodeType::NodeClass));
+        assert!(NodeType::NodeEnum.can_contain(NodeType::NodeEnumConstant));
+        assert!(!NodeType::NodeMethod.can_contain(NodeType::NodeField));
+        assert!(NodeType::NodeSymbol.can_contain(NodeType::NodeMethod));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_node_hierarchy() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .postfix("() const")
+            .parent(shape)
+            .commit()
+            .await?;
+
+        let hierarchy = db.get_node_hierarchy(area).await?;
+        assert_eq!(hierarchy.size(), 2);
+        assert_eq!(hierarchy.names()[0].name(), Some("Shape"));
+        assert_eq!(db.get_qualified_name(area).await?, "Shape::area() const");
+
+        assert_eq!(
+            db.get_qualified_name(area + 100).await,
+            Err(SourcetrailError::ParentNotFound(area + 100))
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_ref_at() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("shape.h").commit().await?;
+        let shape = db.record_class().name("Shape").commit().await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+
+        let edge_id = db
+            .record_ref_at(
+                circle,
+                shape,
+                EdgeType::Inheritance,
+                file_id,
+                (3, 16, 3, 20),
+            )
+            .await?;
+
+        let edge = EdgeDAO::get(&db.database, edge_id).await?.expect("edge");
+        assert_eq!(edge.type_(), EdgeType::Inheritance);
+        assert_eq!(edge.source_id(), circle);
+        assert_eq!(edge.target_id(), shape);
+
+        let locations = SourceLocationDAO::list_by_element(&db.database, edge_id).await?;
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].file_node_id(), file_id);
+        assert_eq!(locations[0].start_column(), 16);
+        assert_eq!(locations[0].location_type(), SourceLocationType::Token);
+
+        assert_eq!(
+            db.record_ref_at(circle, shape, EdgeType::Usage, file_id, (4, 9, 4, 2))
+                .await,
+            Err(SourcetrailError::InvalidSourceRange)
+        );
+        assert_eq!(db.edge_count().await?, 1);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_ref_by_name() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let main = db.record_function().name("main").commit().await?;
+
+        let caller = NameHierarchy::from_cxx_qualified_name("main")?;
+        let callee = NameHierarchy::from_cxx_qualified_name("std::sort")?;
+        let edge_id = db
+            .record_ref_by_name(&caller, &callee, EdgeType::Call)
+            .await?;
+
+        let edge = EdgeDAO::get(&db.database, edge_id).await?.expect("edge");
+        assert_eq!(edge.type_(), EdgeType::Call);
+        assert_eq!(edge.source_id(), main);
+
+        let sort = db.node_id_by_name(&callee).await?.expect("implicit symbol");
+        assert_eq!(edge.target_id(), sort);
+        let symbol = SymbolDAO::get(&db.database, sort).await?.expect("symbol");
+        assert_eq!(symbol.definition_kind(), SymbolType::Implicit);
+
+        // a second reference reuses the recorded endpoint
+        db.record_ref_by_name(&caller, &callee, EdgeType::Call)
+            .await?;
+        assert_eq!(db.nodes_of_type(NodeType::NodeSymbol).await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_unsolved_references() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let main = db.record_function().name("main").commit().await?;
+
+        let printf = db.unsolved_symbol("printf").await?;
+        assert_eq!(db.unsolved_symbol("printf").await?, printf);
+
+        let references = (1..=10)
+            .map(|line| (main, file_id, line, 5, line, 10))
+            .collect::<Vec<_>>();
+        let ids = db
+            .record_unsolved_references(printf, EdgeType::Call, &references)
+            .await?;
+        assert_eq!(ids.len(), 10);
+
+        let edges = EdgeDAO::list(&db.database).await?;
+        assert_eq!(edges.len(), 10);
+        assert!(edges.iter().all(|edge| edge.target_id() == printf));
+        assert_eq!(db.node_count().await?, 3);
+        assert!(SourceLocationDAO::list(&db.database)
+            .await?
+            .iter()
+            .all(|loc| loc.location_type() == SourceLocationType::Unsolved));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_caller_graph() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let main = db.record_function().name("main").commit().await?;
+        let draw = db.record_function().name("draw").commit().await?;
+        let area = db.record_function().name("area").commit().await?;
+        db.record_ref_usage(main, draw).await?;
+
+        for (caller, line) in [(main, 3), (draw, 10), (main, 5)] {
+            let call = db.record_ref_call(caller, area).await?;
+            db.record_reference_location()
+                .symbol(call)
+                .file(file_id)
+                .start_position(line, 5)
+                .end_position(line, 9)
+                .commit()
+                .await?;
+        }
+
+        let graph = db.get_caller_graph(area).await?;
+        assert_eq!(
+            graph
+                .iter()
+                .map(|(node, locs)| (
+                    node.id(),
+                    locs.iter().map(|loc| loc.start_line()).collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            [(main, vec![3, 5]), (draw, vec![10])]
+        );
+        assert!(db.get_caller_graph(draw).await?.is_empty());
+
+        let graph = db.get_callee_graph(main).await?;
+        assert_eq!(graph.len(), 1);
+        assert_eq!(graph[0].0.id(), area);
+        assert_eq!(graph[0].1.len(), 2);
+        assert!(db.get_callee_graph(area).await?.is_empty());
+
+        let callers = db.find_callers_of(area).await?;
+        assert_eq!(
+            callers
+                .iter()
+                .map(|edge| edge.source_id())
+                .collect::<Vec<_>>(),
+            [main, draw, main]
+        );
+        assert!(db
+            .find_callees_of(main)
+            .await?
+            .iter()
+            .all(|edge| edge.target_id() == area));
+
+        let callers = db.find_callers_of_with_locations(area).await?;
+        assert_eq!(
+            callers
+                .iter()
+                .map(|(edge, loc)| (edge.source_id(), loc.start_line()))
+                .collect::<Vec<_>>(),
+            [(main, 3), (draw, 10), (main, 5)]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_inheritance_chain() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let object = db.record_class().name("Object").commit().await?;
+        let shape = db.record_class().name("Shape").commit().await?;
+        let drawable = db.record_interface().name("Drawable").commit().await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+        db.record_ref_inheritance(shape, object).await?;
+        db.record_ref_inheritance(circle, drawable).await?;
+        db.record_ref_inheritance(circle, shape).await?;
+
+        let ids = |chains: Vec<Vec<Node>>| {
+            chains
+                .iter()
+                .map(|chain| chain.iter().map(|node| node.id()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(db.get_inheritance_chain(circle).await?),
+            [vec![circle, drawable], vec![circle, shape, object]]
+        );
+        assert_eq!(ids(db.get_inheritance_chain(object).await?), [vec![object]]);
+
+        db.record_ref_inheritance(object, circle).await?;
+        let err = db.get_inheritance_chain(circle).await.unwrap_err();
+        assert!(matches!(err, SourcetrailError::Builder(_)));
+        assert!(err.to_string().contains("inheritance cycle"));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_list_overriding_methods() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let shape_area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        let shape_draw = db
+            .record_method()
+            .name("draw")
+            .parent(shape)
+            .commit()
+            .await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+        let circle_area = db
+            .record_method()
+            .name("area")
+            .parent(circle)
+            .commit()
+            .await?;
+        let circle_draw = db
+            .record_method()
+            .name("draw")
+            .parent(circle)
+            .commit()
+            .await?;
+        db.record_method()
+            .name("radius")
+            .parent(circle)
+            .commit()
+            .await?;
+        db.record_ref_inheritance(circle, shape).await?;
+        db.record_ref_override(circle_area, shape_area).await?;
+        db.record_ref_override(circle_draw, shape_draw).await?;
+
+        let overrides = db.list_overriding_methods(circle).await?;
+        assert_eq!(
+            overrides
+                .iter()
+                .map(|(method, overridden)| (method.id(), overridden.id()))
+                .collect::<Vec<_>>(),
+            [(circle_area, shape_area), (circle_draw, shape_draw)]
+        );
+        assert!(db.list_overriding_methods(shape).await?.is_empty());
+
+        // declaration followed by definition
+        let redefined = db
+            .record_method()
+            .name("area")
+            .parent(circle)
+            .commit()
+            .await?;
+        assert_eq!(redefined, circle_area);
+        assert_eq!(
+            db.list_overriding_methods(circle)
+                .await?
+                .iter()
+                .map(|(method, overridden)| (method.id(), overridden.id()))
+                .collect::<Vec<_>>(),
+            [(circle_area, shape_area), (circle_draw, shape_draw)]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_get_type_hierarchy() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+        let square = db.record_class().name("Square").commit().await?;
+        let rounded = db.record_class().name("RoundedSquare").commit().await?;
+        db.record_ref_inheritance(circle, shape).await?;
+        db.record_ref_inheritance(square, shape).await?;
+        db.record_ref_inheritance(rounded, square).await?;
+
+        let tree = db.get_type_hierarchy(shape).await?;
+        assert_eq!(tree.node().id(), shape);
+        assert_eq!(
+            tree.children()
+                .iter()
+                .map(|child| child.node().id())
+                .collect::<Vec<_>>(),
+            [circle, square]
+        );
+        assert!(tree.children()[0].children().is_empty());
+        assert_eq!(tree.children()[1].children()[0].node().id(), rounded);
+
+        assert!(db.get_type_hierarchy(rounded).await?.children().is_empty());
+
+        db.record_ref_inheritance(shape, rounded).await?;
+        assert!(matches!(
+            db.get_type_hierarchy(shape).await,
+            Err(SourcetrailError::Builder(_))
+        ));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_edges_with_locations() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let main = db.record_function().name("main").commit().await?;
+        let area = db.record_function().name("area").commit().await?;
+        let draw = db.record_function().name("draw").commit().await?;
+
+        let to_area = db
+            .record_ref_at(main, area, EdgeType::Call, file_id, (3, 5, 3, 9))
+            .await?;
+        db.record_reference_location()
+            .symbol(to_area)
+            .file(file_id)
+            .start_position(4, 5)
+            .end_position(4, 9)
+            .commit()
+            .await?;
+        let to_draw = db.record_ref_call(main, draw).await?;
+        db.record_ref_usage(main, area).await?;
+
+        let calls = db.edges_with_locations(EdgeType::Call).await?;
+        assert_eq!(
+            calls
+                .iter()
+                .map(|(edge, loc)| (edge.id(), loc.as_ref().map(|loc| loc.start_line())))
+                .collect::<Vec<_>>(),
+            [(to_area, Some(3)), (to_area, Some(4)), (to_draw, None)]
+        );
+        assert!(db
+            .edges_with_locations(EdgeType::Override)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_files_referencing() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape_h = db.record_file().path("shape.h").commit().await?;
+        let main_c = db.record_file().path("main.c").commit().await?;
+        let draw_c = db.record_file().path("draw.c").commit().await?;
+        let main = db.record_function().name("main").commit().await?;
+        let draw = db.record_function().name("draw").commit().await?;
+        let area = db.record_function().name("area").commit().await?;
+
+        for (caller, file_id, line) in [(main, main_c, 3), (main, main_c, 5), (draw, draw_c, 7)] {
+            db.record_ref_at(caller, area, EdgeType::Call, file_id, (line, 5, line, 9))
+                .await?;
+        }
+
+        let files = db.files_referencing(area).await?;
+        assert_eq!(
+            files.iter().map(|file| file.id()).collect::<Vec<_>>(),
+            [main_c, draw_c]
+        );
+        assert!(!files.iter().any(|file| file.id() == shape_h));
+        assert!(db.files_referencing(shape_h).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_largest_files_by_line_count() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let small = db.record_file().path("a.h").content("x\n").commit().await?;
+        let large = db
+            .record_file()
+            .path("b.c")
+            .content("x\ny\nz\n")
+            .commit()
+            .await?;
+        let medium = db
+            .record_file()
+            .path("c.c")
+            .content("x\ny\n")
+            .commit()
+            .await?;
+        let tied = db
+            .record_file()
+            .path("d.c")
+            .content("x\ny\n")
+            .commit()
+            .await?;
+
+        let ids = |files: Vec<File>| files.iter().map(|file| file.id()).collect::<Vec<_>>();
+        assert_eq!(
+            ids(db.largest_files_by_line_count(u64::MAX).await?),
+            [large, medium, tied, small]
+        );
+        assert_eq!(
+            ids(db.largest_files_by_line_count(2).await?),
+            [large, medium]
+        );
+        assert!(db.largest_files_by_line_count(0).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_references_in_file() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let main = db.record_function().name("main").commit().await?;
+        let area = db.record_function().name("area").commit().await?;
+        let call = db.record_ref_call(main, area).await?;
+
+        db.record_symbol_location()
+            .symbol(main)
+            .file(file_id)
+            .start_position(1, 5)
+            .end_position(1, 8)
+            .commit()
+            .await?;
+        db.record_reference_location()
+            .symbol(call)
+            .file(file_id)
+            .start_position(3, 5)
+            .end_position(3, 8)
+            .commit()
+            .await?;
+        let unsolved = db
+            .record_reference_to_unsolved_symbol()
+            .symbol(main)
+            .reference_type(EdgeType::Call)
+            .file(file_id)
+            .start_position(2, 5)
+            .end_position(2, 10)
+            .commit()
+            .await?;
+
+        let references = db.references_in_file(file_id).await?;
+        assert_eq!(
+            references
+                .iter()
+                .map(|(edge, loc)| (edge.id(), loc.start_line(), loc.location_type()))
+                .collect::<Vec<_>>(),
+            [
+                (unsolved, 2, SourceLocationType::Unsolved),
+                (call, 3, SourceLocationType::Token),
+            ]
+        );
+        assert_eq!(references[1].0.target_id(), area);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_components_for() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let caller = db.record_function().name("main").commit().await?;
+        let callee = db.record_function().name("area").commit().await?;
+        let call = db.record_ref_call(caller, callee).await?;
+
+        assert!(db.components_for(call).await?.is_empty());
+
+        db.record_reference_is_ambiguous(call).await?;
+
+        let components = db.components_for(call).await?;
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].elem_id(), call);
+        assert_eq!(
+            components[0].component_type(),
+            ElementComponentType::IsAmbiguous
+        );
+        assert!(db.components_for(caller).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_open_rejects_storage_version() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("test");
+
+        let mut db = SourcetrailDB::create(&path).await?;
+        db.set_meta_value("storage_version", "20").await?;
+        db.close().await?;
+
+        assert_eq!(
+            SourcetrailDB::open(&path, false).await.err(),
+            Some(SourcetrailError::UnsupportedStorageVersion {
+                found: 20,
+                expected: 25,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_verify_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let (dir, db) = temp_db().await?;
+        db.verify_schema().await?;
+
+        SqliteHelper::exec(
+            &db.database,
+            "ALTER TABLE node DROP COLUMN serialized_name;",
+            SqliteArguments::default(),
+        )
+        .await?;
+        assert_eq!(
+            db.verify_schema().await,
+            Err(SourcetrailError::SchemaMismatch(
+                "missing column node.serialized_name".to_owned()
+            ))
+        );
+
+        let foreign = dir.path().join("foreign.srctrldb");
+        let pool = SqliteHelper::connect(foreign.to_string_lossy().as_ref()).await?;
+        SqliteHelper::exec(
+            &pool,
+            "CREATE TABLE element(id TEXT);",
+            SqliteArguments::default(),
+        )
+        .await?;
+        pool.close().await;
+
+        let db = SourcetrailDB::open(&foreign, false).await?;
+        assert_eq!(
+            db.verify_schema().await,
+            Err(SourcetrailError::SchemaMismatch(
+                "column element.id has type TEXT, expected INTEGER".to_owned()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_meta_values() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        assert_eq!(db.get_storage_version().await?, Some(25));
+        assert_eq!(db.meta("storage_version").await?.as_deref(), Some("25"));
+        assert_eq!(
+            db.get_project_settings().await?,
+            Some(fs::read_to_string(db.project_path())?)
+        );
+        assert_eq!(db.get_meta_value("indexer").await?, None);
+
+        db.set_meta_value("indexer", "v1").await?;
+        db.set_meta_value("indexer", "v2").await?;
+        assert_eq!(db.get_meta_value("indexer").await?.as_deref(), Some("v2"));
+
+        let count = MetaDAO::list(&db.database)
+            .await?
+            .iter()
+            .filter(|m| m.key() == "indexer")
+            .count();
+        assert_eq!(count, 1);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_counts() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let class_id = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
             .parent(class_id)
             .commit()
             .await?;
+        let helper = db.record_function().name("helper").commit().await?;
+        db.record_ref_call(area, helper).await?;
+        db.record_file().path("shape.cpp").commit().await?;
 
-        db.record_ref_usage(meth_id, field_id).await?;
+        assert_eq!(db.node_count().await?, 4);
+        assert_eq!(db.edge_count().await?, 2);
+        assert_eq!(db.symbol_count().await?, 3);
+        assert_eq!(db.file_count().await?, 1);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_members() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let class_id = db.record_class().name("Shape").commit().await?;
+        let members = (0..50)
+            .map(|i| {
+                let kind = if i % 2 == 0 {
+                    NodeType::NodeField
+                } else {
+                    NodeType::NodeMethod
+                };
+                (kind, NameElement::builder().name(format!("m{i}")).build())
+            })
+            .collect::<Vec<_>>();
+
+        let fetches = PARENT_FETCHES.with(|fetches| fetches.get());
+        let ids = db.record_members(class_id, &members).await?;
+        assert_eq!(PARENT_FETCHES.with(|fetches| fetches.get()), fetches + 1);
+        assert_eq!(ids.len(), 50);
+        assert_eq!(db.symbol_count().await?, 51);
+
+        let edges = EdgeDAO::list(&db.database).await?;
+        let mut targets = edges
+            .iter()
+            .filter(|edge| edge.type_() == EdgeType::Member && edge.source_id() == class_id)
+            .map(|edge| edge.target_id())
+            .collect::<Vec<_>>();
+        targets.sort();
+        assert_eq!(targets, ids);
+
+        for (i, id) in ids.iter().enumerate() {
+            let node = NodeDAO::get(&db.database, *id).await?.expect("member");
+            assert_eq!(node.type_(), members[i].0);
+            let hierarchy = NameHierarchy::deserialize_name(node.name())?;
+            assert_eq!(hierarchy.names().len(), 2);
+            assert_eq!(hierarchy.names()[0].name(), Some("Shape"));
+            assert_eq!(hierarchy.names()[1].name(), Some(format!("m{i}").as_str()));
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_enum_with_constants() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let (enum_id, constant_ids) = db
+            .record_enum_with_constants("Color", &["Red", "Green", "Blue"])
+            .await?;
+        assert_eq!(constant_ids.len(), 3);
+
+        let node = NodeDAO::get(&db.database, enum_id).await?.expect("enum");
+        assert_eq!(node.type_(), NodeType::NodeEnum);
+
+        for (id, name) in constant_ids.iter().zip(["Red", "Green", "Blue"]) {
+            let node = NodeDAO::get(&db.database, *id).await?.expect("constant");
+            assert_eq!(node.type_(), NodeType::NodeEnumConstant);
+            let hierarchy = NameHierarchy::deserialize_name(node.name())?;
+            assert_eq!(hierarchy.names()[0].name(), Some("Color"));
+            assert_eq!(hierarchy.names()[1].name(), Some(name));
+        }
+
+        let edges = EdgeDAO::list(&db.database).await?;
+        assert_eq!(edges.len(), 3);
+        for (edge, id) in edges.iter().zip(&constant_ids) {
+            assert_eq!(edge.type_(), EdgeType::Member);
+            assert_eq!(edge.source_id(), enum_id);
+            assert_eq!(edge.target_id(), *id);
+        }
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_scaffolding_type() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let hierarchy = |names: &[&str]| {
+            NameHierarchy::new(
+                NameHierarchy::NAME_DELIMITER_CXX,
+                names
+                    .iter()
+                    .map(|name| NameElement::builder().name(*name).build()),
+            )
+        };
+
+        let plain = db
+            .record_symbol_hierarchy(
+                &hierarchy(&["std", "vector"])?,
+                NodeType::NodeClass,
+                NodeType::NodeSymbol,
+            )
+            .await?;
+
+        let class_id = db
+            .record_symbol_hierarchy(
+                &hierarchy(&["a", "b", "C"])?,
+                NodeType::NodeClass,
+                NodeType::NodeNamespace,
+            )
+            .await?;
+
+        let nodes = NodeDAO::list(&db.database).await?;
+        let types = nodes
+            .iter()
+            .map(|node| {
+                let hierarchy = NameHierarchy::deserialize_name(node.name()).expect("name");
+                let name = hierarchy
+                    .names()
+                    .iter()
+                    .map(|elt| elt.name().unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join("::");
+                (name, node.type_())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                ("std".to_owned(), NodeType::NodeSymbol),
+                ("std::vector".to_owned(), NodeType::NodeClass),
+                ("a".to_owned(), NodeType::NodeNamespace),
+                ("a::b".to_owned(), NodeType::NodeNamespace),
+                ("a::b::C".to_owned(), NodeType::NodeClass),
+            ]
+        );
+
+        let symbols = SymbolDAO::list(&db.database).await?;
+        assert_eq!(
+            symbols.iter().map(|sym| sym.id()).collect::<Vec<_>>(),
+            [plain, class_id]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_list_nodes_matching() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        let shape_area = db.record_function().name("shape_area").commit().await?;
+
+        let ids = |nodes: Vec<Node>| nodes.iter().map(|node| node.id()).collect::<Vec<_>>();
+
+        assert_eq!(
+            ids(db.list_nodes_matching_pattern("%area%").await?),
+            [area, shape_area]
+        );
+        assert_eq!(
+            ids(db.list_nodes_matching_pattern("%\tmShape\ts\tp").await?),
+            [shape]
+        );
+
+        #[cfg(feature = "regex")]
+        assert_eq!(
+            ids(db
+                .list_nodes_matching_regex(&regex::Regex::new("^Shape::")?)
+                .await?),
+            [area]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_nodes_of_kind_in_file() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape_h = db.record_file().path("shape.h").commit().await?;
+        let main_c = db.record_file().path("main.c").commit().await?;
+        let shape = db.record_class().name("Shape").commit().await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+        let area = db.record_function().name("area").commit().await?;
+
+        for (id, file_id, line) in [
+            (shape, shape_h, 1),
+            (shape, shape_h, 4),
+            (area, shape_h, 2),
+            (circle, main_c, 1),
+        ] {
+            db.record_symbol_location()
+                .symbol(id)
+                .file(file_id)
+                .start_position(line, 1)
+                .end_position(line, 5)
+                .commit()
+                .await?;
+        }
+
+        let classes = db
+            .nodes_of_kind_in_file(shape_h, NodeType::NodeClass)
+            .await?;
+        assert_eq!(
+            classes.iter().map(|node| node.id()).collect::<Vec<_>>(),
+            [shape]
+        );
+        assert!(db
+            .nodes_of_kind_in_file(main_c, NodeType::NodeFunction)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_list_nodes_lossy() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+        let square = db.record_class().name("Square").commit().await?;
+
+        SqliteHelper::exec(
+            &db.database,
+            &format!("UPDATE node SET type = 3 WHERE id = {circle};"),
+            SqliteArguments::default(),
+        )
+        .await?;
+
+        assert!(NodeDAO::list(&db.database).await.is_err());
+
+        let (nodes, skipped) = NodeDAO::list_lossy(&db.database).await?;
+        assert_eq!(
+            nodes.iter().map(|node| node.id()).collect::<Vec<_>>(),
+            [shape, square]
+        );
+        assert_eq!(skipped, 1);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_nodes_of_type() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let circle = db.record_class().name("Circle").commit().await?;
+        db.record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+
+        let classes = db.nodes_of_type(NodeType::NodeClass).await?;
+        assert_eq!(
+            classes.iter().map(|node| node.id()).collect::<Vec<_>>(),
+            [shape, circle]
+        );
+        assert!(db.nodes_of_type(NodeType::NodeEnum).await?.is_empty());
+
+        let mut stream = db.stream_nodes();
+        let mut ids = Vec::new();
+        while let Some(node) = stream.next().await {
+            ids.push(node?.id());
+        }
+        assert_eq!(ids.len(), 3);
+        assert_eq!(&ids[..2], [shape, circle]);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_stream_files() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let mut ids = Vec::new();
+        for path in ["a.c", "b.c", "c.c"] {
+            ids.push(db.record_file().path(path).commit().await?);
+        }
+
+        SqliteHelper::exec(
+            &db.database,
+            &format!(
+                "UPDATE file SET modification_time = 'yesterday' WHERE id = {};",
+                ids[1]
+            ),
+            SqliteArguments::default(),
+        )
+        .await?;
+
+        let mut stream = db.stream_files();
+        let (mut files, mut errors) = (Vec::new(), 0);
+        while let Some(file) = stream.next().await {
+            match file {
+                Ok(file) => files.push(file.id()),
+                Err(SourcetrailError::TypeConversion(_)) => errors += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        assert_eq!(files, [ids[0], ids[2]]);
+        assert_eq!(errors, 1);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_symbol_at() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("shape.cpp").commit().await?;
+        let location = |file_id| {
+            SourceLocation::builder()
+                .file_node_id(file_id)
+                .start_line(3)
+                .start_column(7)
+                .end_line(3)
+                .end_column(11)
+                .location_type(SourceLocationType::Token)
+                .build()
+        };
+
+        let hierarchy = NameHierarchy::from_cxx_qualified_name("geo::Shape")?;
+        let shape = db
+            .record_symbol_at(
+                &hierarchy,
+                NodeType::NodeClass,
+                location(file_id)?,
+                Some("cpp"),
+            )
+            .await?;
+
+        let file = FileDAO::get(&db.database, file_id).await?.expect("file");
+        assert_eq!(file.language(), "cpp");
+        let locations = SourceLocationDAO::list(&db.database).await?;
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].start_column(), 7);
+        assert_eq!(
+            NodeDAO::get(&db.database, shape)
+                .await?
+                .expect("node")
+                .type_(),
+            NodeType::NodeClass
+        );
+
+        // a missing file rolls back the symbol as well
+        let circle = NameHierarchy::from_cxx_qualified_name("geo::Circle")?;
+        assert!(db
+            .record_symbol_at(
+                &circle,
+                NodeType::NodeClass,
+                location(file_id + 100)?,
+                Some("cpp")
+            )
+            .await
+            .is_err());
+        assert_eq!(db.node_id_by_name(&circle).await?, None);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_source_locations_batch() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let sym_id = db.record_global_variable().name("x").commit().await?;
+
+        let ranges = (1..=1000)
+            .map(|line| (line, 5, line, 6, SourceLocationType::Token))
+            .collect::<Vec<_>>();
+        db.record_source_locations_batch(sym_id, file_id, &ranges)
+            .await?;
+        assert_eq!(
+            OccurrenceDAO::list_by_element(&db.database, sym_id)
+                .await?
+                .len(),
+            1000
+        );
+
+        assert_eq!(
+            db.record_source_locations_batch(
+                sym_id,
+                file_id,
+                &[
+                    (1001, 5, 1001, 6, SourceLocationType::Token),
+                    (1002, 6, 1002, 5, SourceLocationType::Token),
+                ],
+            )
+            .await,
+            Err(SourcetrailError::InvalidSourceRange)
+        );
+        assert_eq!(SourceLocationDAO::list(&db.database).await?.len(), 1000);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_directory() -> Result<(), Box<dyn std::error::Error>> {
+        let (dir, mut db) = temp_db().await?;
+
+        let root = dir.path().join("src");
+        fs::create_dir_all(root.join("geo"))?;
+        fs::write(root.join("main.c"), "int main() {}\n")?;
+        fs::write(root.join("geo/shape.h"), "struct Shape;\n")?;
+        fs::write(root.join("geo/shape.c"), [0xff, b'\n'])?;
+        fs::write(root.join("README.md"), "# shapes\n")?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&root, root.join("geo/loop"))?;
+
+        let (ids, skipped) = db.record_directory(&root, &["c", ".h"]).await?;
+        assert!(skipped.is_empty());
+
+        let mut paths = Vec::new();
+        for id in ids {
+            let file = FileDAO::get(&db.database, id).await?.expect("file");
+            paths.push(file.path().strip_prefix(&root)?.to_path_buf());
+        }
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("geo/shape.c"),
+                PathBuf::from("geo/shape.h"),
+                PathBuf::from("main.c"),
+            ]
+        );
+
+        let (ids, skipped) = db
+            .record_directory(&dir.path().join("missing"), &["c"])
+            .await?;
+        assert!(ids.is_empty());
+        assert_eq!(skipped.len(), 1);
+        assert!(matches!(skipped[0].1, SourcetrailError::FileRecorder(_)));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_file_from_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let (dir, mut db) = temp_db().await?;
+
+        let generated = b"int main() {\n    return 0;\n}\n";
+        let file_id = db
+            .record_file()
+            .path("generated.c")
+            .content_bytes(generated)
+            .commit()
+            .await?;
+        let file = FileDAO::get(&db.database, file_id).await?.expect("file");
+        assert_eq!(file.line_count(), 3);
+
+        let latin1 = dir.path().join("latin1.c");
+        fs::write(&latin1, b"// caf\xe9\nint x;\n")?;
+        let file_id = db.record_file().commit_file(&latin1).await?;
+        let file = FileDAO::get(&db.database, file_id).await?.expect("file");
+        assert_eq!(file.line_count(), 2);
+        let content = FileContentDAO::get(&db.database, file_id)
+            .await?
+            .expect("content");
+        assert_eq!(content.content(), "// caf\u{fffd}\nint x;\n");
+
+        let blob_id = db
+            .record_file()
+            .path("blob.bin")
+            .commit_bytes(&[0xff, b'\n', b'a'])
+            .await?;
+        let file = FileDAO::get(&db.database, blob_id).await?.expect("file");
+        assert_eq!(file.line_count(), 2);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_reference_degrees() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let shape = db.record_class().name("Shape").commit().await?;
+        let area = db
+            .record_method()
+            .name("area")
+            .parent(shape)
+            .commit()
+            .await?;
+        let main = db.record_function().name("main").commit().await?;
+        let draw = db.record_function().name("draw").commit().await?;
+        let log = db.record_function().name("log").commit().await?;
+
+        db.record_ref_call(main, area).await?;
+        db.record_ref_call(draw, area).await?;
+        db.record_ref_call(area, log).await?;
+        db.record_ref_type_usage(draw, shape).await?;
+
+        let degrees = db.reference_degrees().await?;
+        assert_eq!(degrees[&area], (2, 1));
+        assert_eq!(degrees[&shape], (1, 0));
+        assert_eq!(degrees[&draw], (0, 2));
+        assert_eq!(degrees.len(), 5);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_streams() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("main.c").commit().await?;
+        let caller = db.record_function().name("main").commit().await?;
+        let callee = db.record_function().name("area").commit().await?;
+        let call = db.record_ref_call(caller, callee).await?;
+        db.record_symbol_location()
+            .symbol(caller)
+            .file(file_id)
+            .start_position(1, 5)
+            .end_position(1, 8)
+            .commit()
+            .await?;
+        db.record_error()
+            .message("expected ';'")
+            .file(file_id)
+            .start_position(2, 1)
+            .end_position(2, 2)
+            .commit()
+            .await?;
+
+        async fn drain<T>(
+            stream: impl Stream<Item = Result<T, SourcetrailError>>,
+        ) -> Result<Vec<T>, SourcetrailError> {
+            let mut stream = Box::pin(stream);
+            let mut items = Vec::new();
+            while let Some(item) = stream.next().await {
+                items.push(item?);
+            }
+            Ok(items)
+        }
+
+        let edges = drain(db.stream_edges()).await?;
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].id(), call);
+
+        let errors = drain(db.stream_errors()).await?;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message(), "expected ';'");
+
+        let locations = drain(db.stream_source_locations()).await?;
+        assert_eq!(locations.len(), 2);
+        assert!(locations.iter().all(|loc| loc.file_node_id() == file_id));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_record_symbol_hierarchy() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let hierarchy = NameHierarchy::new(
+            NameHierarchy::NAME_DELIMITER_CXX,
+            ["a", "b", "C"].map(|name| NameElement::builder().name(name).build()),
+        )?;
+        let class_id = db
+            .record_symbol_hierarchy(&hierarchy, NodeType::NodeClass, NodeType::NodeNamespace)
+            .await?;
+
+        let nodes = NodeDAO::list(&db.database).await?;
+        assert_eq!(
+            nodes.iter().map(|node| node.type_()).collect::<Vec<_>>(),
+            [
+                NodeType::NodeNamespace,
+                NodeType::NodeNamespace,
+                NodeType::NodeClass
+            ]
+        );
+        assert_eq!(nodes[2].id(), class_id);
+
+        let edges = EdgeDAO::list(&db.database).await?;
+        assert_eq!(
+            edges
+                .iter()
+                .map(|edge| (edge.source_id(), edge.target_id()))
+                .collect::<Vec<_>>(),
+            [(nodes[0].id(), nodes[1].id()), (nodes[1].id(), class_id)]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_total_line_count() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        assert_eq!(db.total_line_count().await?, 0);
+
+        db.record_file()
+            .path("shape.h")
+            .content("class Shape;\n")
+            .commit()
+            .await?;
+        db.record_file()
+            .path("shape.cpp")
+            .content("#include \"shape.h\"\n\nint main() {}\n")
+            .commit()
+            .await?;
+        db.record_file()
+            .path("vendor.h")
+            .content("a\nb\nc\n")
+            .indexed(false)
+            .commit()
+            .await?;
+
+        assert_eq!(db.total_line_count().await?, 4);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_files_of_symbol() -> Result<(), Box<dyn std::error::Error>> {
+        let (_dir, mut db) = temp_db().await?;
+
+        let header = db.record_file().path("shape.h").commit().await?;
+        let source = db.record_file().path("shape.cpp").commit().await?;
+        let class_id = db.record_class().name("Shape").commit().await?;
+
+        for (file_id, line) in [(header, 1), (header, 5), (source, 3)] {
+            db.record_symbol_location()
+                .symbol(class_id)
+                .file(file_id)
+                .start_position(line, 7)
+                .end_position(line, 12)
+                .commit()
+                .await?;
+        }
+
+        assert_eq!(db.files_of_symbol(class_id).await?, [header, source]);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_document_symbols() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::lsp::{Position, SymbolKind};
+
+        let (_dir, mut db) = temp_db().await?;
+
+        let file_id = db.record_file().path("shape.cpp").commit().await?;
+        let class_id = db.record_class().name("Shape").commit().await?;
+        let method_id = db
+            .record_method()
+            .name("area")
+            .parent(class_id)
+            .commit()
+            .await?;
+
+        for (id, kind, start, end) in [
+            (class_id, SourceLocationType::Scope, (1, 1), (5, 1)),
+            (class_id, SourceLocationType::Token, (1, 7), (1, 11)),
+            (method_id, SourceLocationType::Scope, (2, 5), (4, 5)),
+            (method_id, SourceLocationType::Token, (2, 9), (2, 12)),
+        ] {
+            db.record_location(kind)
+                .symbol(id)
+                .file(file_id)
+                .start_position(start.0, start.1)
+                .end_position(end.0, end.1)
+                .commit()
+                .await?;
+        }
+
+        let symbols = db.document_symbols(file_id).await?;
+        assert_eq!(symbols.len(), 1);
+
+        let class = &symbols[0];
+        assert_eq!(class.name(), "Shape");
+        assert_eq!(class.kind(), SymbolKind::Class);
+        assert_eq!(
+            class.range(),
+            Range::new(Position::new(0, 0), Position::new(4, 1))
+        );
+        assert_eq!(
+            class.selection_range(),
+            Range::new(Position::new(0, 6), Position::new(0, 11))
+        );
+        assert_eq!(class.children().len(), 1);
+
+        let method = &class.children()[0];
+        assert_eq!(method.name(), "area");
+        assert_eq!(method.kind(), SymbolKind::Method);
+        assert_eq!(
+            method.range(),
+            Range::new(Position::new(1, 4), Position::new(3, 5))
+        );
+        assert!(method.children().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_name_display() -> Result<(), SourcetrailError> {
+        let cxx = NameHierarchy::new(
+            NameHierarchy::NAME_DELIMITER_CXX,
+            [
+                NameElement::builder().name("std").build(),
+                NameElement::builder().name("vector").postfix("<T>").build(),
+                NameElement::builder()
+                    .prefix("size_t ")
+                    .name("size")
+                    .postfix("() const")
+                    .build(),
+            ],
+        )?;
+        assert_eq!(
+            cxx.to_display_string(),
+            "std::vector<T>::size_t size() const"
+        );
+        assert_eq!(cxx.names()[1].to_string(), "vector<T>");
+
+        let java = NameHierarchy::from_java_qualified_name("com.example.MyClass")?;
+        assert_eq!(java.to_string(), "com.example.MyClass");
+
+        let file = NameHierarchy::new(
+            NameHierarchy::NAME_DELIMITER_FILE,
+            [NameElement::builder().name("/src/main.c").build()],
+        )?;
+        assert_eq!(file.to_string(), "/src/main.c");
+
+        let round_trip = NameHierarchy::deserialize_name(cxx.serialize_name()?)?;
+        assert_eq!(round_trip.to_string(), cxx.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_qualified_name() -> Result<(), SourcetrailError> {
+        let cxx = NameHierarchy::from_cxx_qualified_name("std::vector::iterator")?;
+        assert_eq!(cxx.delimiter(), NameHierarchy::NAME_DELIMITER_CXX);
+        assert_eq!(
+            cxx.names()
+                .iter()
+                .map(|e| e.name().unwrap_or_default())
+                .collect::<Vec<_>>(),
+            ["std", "vector", "iterator"]
+        );
+        assert!(cxx.names().iter().all(|e| e.prefix().is_none()));
+
+        let java = NameHierarchy::from_java_qualified_name(".com.example.MyClass.")?;
+        assert_eq!(java.delimiter(), NameHierarchy::NAME_DELIMITER_JAVA);
+        assert_eq!(java.size(), 3);
+        assert_eq!(java.names()[2].name(), Some("MyClass"));
+
+        assert_eq!(NameHierarchy::from_cxx_qualified_name("::main")?.size(), 1);
+        assert_eq!(
+            NameHierarchy::from_cxx_qualified_name("").err(),
+            Some(SourcetrailError::EmptyNameHierarchy)
+        );
+        assert_eq!(
+            NameHierarchy::from_cxx_qualified_name("::").err(),
+            Some(SourcetrailError::EmptyNameHierarchy)
+        );
+        assert_eq!(
+            NameHierarchy::from_cxx_qualified_name("std::::vector").err(),
+            Some(SourcetrailError::InvalidQualifiedName(
+                "std::::vector".to_owned()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_name_from_parts() -> Result<(), SourcetrailError> {
+        let hierarchy = NameHierarchy::cxx(["foo", "bar", "Baz"])?;
+        assert_eq!(hierarchy.delimiter(), NameHierarchy::NAME_DELIMITER_CXX);
+        assert_eq!(hierarchy.to_display_string(), "foo::bar::Baz");
+
+        let serialized = hierarchy.serialize_name()?;
+        assert_eq!(
+            serialized,
+            NameHierarchy::from_cxx_qualified_name("foo::bar::Baz")?.serialize_name()?
+        );
+
+        let round_trip = NameHierarchy::deserialize_name(&serialized)?;
+        assert_eq!(round_trip.size(), 3);
+        assert_eq!(round_trip.names()[2].name(), Some("Baz"));
+        assert_eq!(round_trip.serialize_name()?, serialized);
+
+        let java = NameHierarchy::java(vec![String::from("com"), String::from("Example")])?;
+        assert_eq!(java.to_display_string(), "com.Example");
+        assert_eq!(
+            NameHierarchy::from_parts("/", Vec::<String>::new()).err(),
+            Some(SourcetrailError::EmptyNameHierarchy)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_bare_name() -> Result<(), SourcetrailError> {
+        let hierarchy = NameHierarchy::deserialize_name("::\tmShape\tnarea\ts\tp() const")?;
+        assert_eq!(hierarchy.size(), 2);
+        assert_eq!(hierarchy.names()[0].name(), Some("Shape"));
+        assert_eq!(hierarchy.names()[0].prefix(), Some(""));
+        assert_eq!(hierarchy.names()[0].postfix(), Some(""));
+        assert_eq!(hierarchy.names()[1].postfix(), Some("() const"));
+
+        let bare = NameHierarchy::deserialize_name("::\tmmain")?;
+        let serialized = bare.serialize_name()?;
+        assert_eq!(serialized, "::\tmmain\ts\tp");
+
+        let round_trip = NameHierarchy::deserialize_name(&serialized)?;
+        assert_eq!(round_trip.delimiter(), "::");
+        assert_eq!(round_trip.names()[0].name(), Some("main"));
+        assert_eq!(round_trip.serialize_name()?, serialized);
+
+        assert_eq!(
+            NameHierarchy::deserialize_name("main\ts\tp").err(),
+            Some(SourcetrailError::Deserialize)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_name_round_trip_with_tabs() -> Result<(), SourcetrailError> {
+        let hierarchy = NameHierarchy::new(
+            "::",
+            [
+                NameElement::builder().name("ns\tmain").build(),
+                NameElement::builder()
+                    .prefix("int\t")
+                    .name("f\tn\ts\tp")
+                    .postfix("(\tchar)")
+                    .build(),
+            ],
+        )?;
+
+        let serialized = hierarchy.serialize_name()?;
+        let round_trip = NameHierarchy::deserialize_name(&serialized)?;
+        assert_eq!(round_trip.delimiter(), "::");
+        assert_eq!(round_trip.size(), 2);
+        assert_eq!(round_trip.names()[0].name(), Some("ns\tmain"));
+        assert_eq!(round_trip.names()[1].prefix(), Some("int\t"));
+        assert_eq!(round_trip.names()[1].name(), Some("f\tn\ts\tp"));
+        assert_eq!(round_trip.names()[1].postfix(), Some("(\tchar)"));
+        assert_eq!(round_trip.serialize_name()?, serialized);
+
+        // names without tabs keep Sourcetrail's plain encoding
+        let plain = NameHierarchy::new("::", [NameElement::builder().name("main").build()])?;
+        assert_eq!(plain.serialize_name()?, "::\tmmain\ts\tp");
 
         Ok(())
     }
+
+    #[test]
+    fn test_kind_names() {
+        for kind in [NodeType::NodeClass, NodeType::NodeEnumConstant] {
+            assert_eq!(kind.to_string().parse::<NodeType>(), Ok(kind));
+        }
+        assert_eq!(NodeType::NodeGlobalVariable.to_string(), "global_variable");
+        assert_eq!("type_usage".parse::<EdgeType>(), Ok(EdgeType::TypeUsage));
+        assert_eq!(SourceLocationType::Scope.to_string(), "scope");
+        assert_eq!(
+            "scope".parse::<SourceLocationType>(),
+            Ok(SourceLocationType::Scope)
+        );
+        assert_eq!(
+            "Class".parse::<NodeType>(),
+            Err(SourcetrailError::UnknownKind("Class".to_owned()))
+        );
+    }
 }
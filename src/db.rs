@@ -1,16 +1,27 @@
 #![allow(unused)]
 
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+
+use async_std::stream::{Stream, StreamExt};
+use async_std::sync::{Mutex, MutexGuard};
 use chrono::NaiveDateTime;
-use sqlx::sqlite::{SqliteArguments, SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{
+    SqliteArguments, SqliteConnectOptions, SqliteConnection, SqlitePoolOptions, SqliteRow,
+};
+use sqlx::{Acquire, Connection, FromRow, Sqlite, SqlitePool, Transaction};
 
-use crate::api::SourcetrailError;
+use crate::api::{JournalMode, OpenOptions, SourcetrailError, Synchronous};
 use crate::types::{
-    Edge as EdgeRepr, Element as ElementRepr, ElementComponent as ElementComponentRepr,
-    Error as ErrorRepr, File as FileRepr, FileContent as FileContentRepr,
-    LocalSymbol as LocalSymbolRepr, Meta as MetaRepr, Node as NodeRepr,
-    Occurrence as OccurrenceRepr, SourceLocation as SourceLocationRepr, Symbol as SymbolRepr,
+    ComponentAccess as ComponentAccessRepr, Edge as EdgeRepr, Element as ElementRepr,
+    ElementComponent as ElementComponentRepr, Error as ErrorRepr, File as FileRepr,
+    FileContent as FileContentRepr, LocalSymbol as LocalSymbolRepr, Meta as MetaRepr,
+    Node as NodeRepr, Occurrence as OccurrenceRepr, SourceLocation as SourceLocationRepr,
+    Symbol as SymbolRepr,
 };
+use crate::types::{EdgeType, ElementComponentType, NodeType, SourceLocationType};
 
 macro_rules! query_args {
     ( $( $arg:expr ),* ) => {
@@ -28,42 +39,202 @@ macro_rules! query_args {
     };
 }
 
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+pub type SharedTransaction = Mutex<Option<Transaction<'static, Sqlite>>>;
+
+// Routes queries through the shared transaction when one is active, and
+// through the pool otherwise.
+pub enum Executor<'a> {
+    Pool(&'a SqlitePool),
+    Transaction(MutexGuard<'a, Option<Transaction<'static, Sqlite>>>),
+}
+
+impl<'a> Executor<'a> {
+    pub async fn new(pool: &'a SqlitePool, transaction: &'a SharedTransaction) -> Self {
+        let guard = transaction.lock().await;
+        if guard.is_some() {
+            Self::Transaction(guard)
+        } else {
+            Self::Pool(pool)
+        }
+    }
+}
+
+pub enum ExecutorConnection<'c> {
+    Pool(PoolConnection<Sqlite>),
+    Transaction(&'c mut SqliteConnection),
+}
+
+impl Deref for ExecutorConnection<'_> {
+    type Target = SqliteConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Transaction(conn) => conn,
+        }
+    }
+}
+
+impl DerefMut for ExecutorConnection<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Transaction(conn) => conn,
+        }
+    }
+}
+
+impl<'c, 'a: 'c> Acquire<'c> for &'c mut Executor<'a> {
+    type Database = Sqlite;
+    type Connection = ExecutorConnection<'c>;
+
+    fn acquire(self) -> BoxFuture<'c, Result<Self::Connection, sqlx::Error>> {
+        match self {
+            Executor::Pool(pool) => {
+                let pool = *pool;
+                Box::pin(async move { Ok(ExecutorConnection::Pool(pool.acquire().await?)) })
+            }
+            Executor::Transaction(tx) => {
+                let conn = &mut **tx.as_mut().expect("active transaction");
+                Box::pin(async move { Ok(ExecutorConnection::Transaction(conn)) })
+            }
+        }
+    }
+
+    fn begin(self) -> BoxFuture<'c, Result<Transaction<'c, Sqlite>, sqlx::Error>> {
+        match self {
+            Executor::Pool(pool) => {
+                let pool = *pool;
+                Box::pin(async move { pool.begin().await })
+            }
+            Executor::Transaction(tx) => tx.as_mut().expect("active transaction").begin(),
+        }
+    }
+}
+
 pub struct SqliteHelper;
 
 impl SqliteHelper {
+    /// Bound parameters per statement that every SQLite build accepts.
+    pub const MAX_VARIABLES: usize = 999;
+
     pub async fn connect(path: &str) -> Result<SqlitePool, SourcetrailError> {
+        Self::connect_with_options(path, &OpenOptions::default()).await
+    }
+
+    pub async fn connect_with_options(
+        path: &str,
+        options: &OpenOptions,
+    ) -> Result<SqlitePool, SourcetrailError> {
+        let connect_options = SqliteConnectOptions::default()
+            .create_if_missing(true)
+            .filename(path);
+
+        // Every pooled connection is configured by the same hook so that
+        // none of them can drift from the requested settings.
+        let pragmas = Self::connection_pragmas(options);
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect_with(
-                SqliteConnectOptions::default()
-                    .create_if_missing(true)
-                    .filename(path),
-            )
+            .max_connections(options.get_max_connections())
+            .after_connect(move |conn, _meta| {
+                let pragmas = pragmas.clone();
+                Box::pin(async move {
+                    for pragma in pragmas {
+                        sqlx::query(&pragma).execute(&mut *conn).await?;
+                    }
+                    Ok(())
+                })
+            })
+            .connect_with(connect_options)
             .await?;
         Ok(pool)
     }
 
+    fn connection_pragmas(options: &OpenOptions) -> Vec<String> {
+        let mut pragmas = vec![String::from("PRAGMA foreign_keys = ON;")];
+
+        if let Some(timeout) = options.get_busy_timeout() {
+            pragmas.push(format!("PRAGMA busy_timeout = {};", timeout.as_millis()));
+        }
+
+        if let Some(mode) = options.get_journal_mode() {
+            let mode = match mode {
+                JournalMode::Delete => "DELETE",
+                JournalMode::Truncate => "TRUNCATE",
+                JournalMode::Persist => "PERSIST",
+                JournalMode::Memory => "MEMORY",
+                JournalMode::Wal => "WAL",
+                JournalMode::Off => "OFF",
+            };
+            pragmas.push(format!("PRAGMA journal_mode = {mode};"));
+        }
+
+        if let Some(synchronous) = options.get_synchronous() {
+            let synchronous = match synchronous {
+                Synchronous::Off => "OFF",
+                Synchronous::Normal => "NORMAL",
+                Synchronous::Full => "FULL",
+                Synchronous::Extra => "EXTRA",
+            };
+            pragmas.push(format!("PRAGMA synchronous = {synchronous};"));
+        }
+
+        pragmas
+    }
+
     pub async fn exec(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         query: &str,
         params: SqliteArguments<'_>,
     ) -> Result<i64, SourcetrailError> {
-        let mut tx = pool.begin().await?;
-        let res = sqlx::query_with(query, params).execute(tx.as_mut()).await?;
-        tx.commit().await?;
+        let mut conn = conn.acquire().await?;
+        let res = sqlx::query_with(query, params).execute(&mut *conn).await?;
         Ok(res.last_insert_rowid())
     }
 
+    pub async fn exec_raw(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        query: &str,
+    ) -> Result<(), SourcetrailError> {
+        let mut conn = conn.acquire().await?;
+        sqlx::query(query).execute(&mut *conn).await?;
+        Ok(())
+    }
+
+    pub async fn vacuum_into(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        path: &str,
+    ) -> Result<(), SourcetrailError> {
+        Self::exec(conn, "VACUUM INTO ?;", query_args![path]).await?;
+        Ok(())
+    }
+
+    /// `(name, declared type)` of each column of `table`; empty if the table
+    /// does not exist.
+    pub async fn table_columns(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        table: &str,
+    ) -> Result<Vec<(String, String)>, SourcetrailError> {
+        Self::fetch::<(String, String)>(
+            conn,
+            "SELECT name, type FROM pragma_table_info(?);",
+            query_args![table],
+        )
+        .await
+    }
+
     pub async fn fetch_one<T>(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         query: &str,
         params: SqliteArguments<'_>,
     ) -> Result<Option<T>, SourcetrailError>
     where
         T: for<'q> FromRow<'q, SqliteRow> + Send + Unpin,
     {
+        let mut conn = conn.acquire().await?;
         let res = sqlx::query_as_with::<_, T, _>(query, params)
-            .fetch_one(pool)
+            .fetch_one(&mut *conn)
             .await;
 
         if matches!(res, Err(sqlx::Error::RowNotFound)) {
@@ -73,16 +244,29 @@ impl SqliteHelper {
         }
     }
 
+    pub async fn count(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        query: &str,
+        params: SqliteArguments<'_>,
+    ) -> Result<i64, SourcetrailError> {
+        let mut conn = conn.acquire().await?;
+        let count = sqlx::query_scalar_with::<_, i64, _>(query, params)
+            .fetch_one(&mut *conn)
+            .await?;
+        Ok(count)
+    }
+
     pub async fn fetch<T>(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         query: &str,
         params: SqliteArguments<'_>,
     ) -> Result<Vec<T>, SourcetrailError>
     where
         T: for<'q> FromRow<'q, SqliteRow> + Send + Unpin,
     {
+        let mut conn = conn.acquire().await?;
         let rows = sqlx::query_as_with::<_, T, _>(query, params)
-            .fetch_all(pool)
+            .fetch_all(&mut *conn)
             .await?;
         Ok(rows)
     }
@@ -102,9 +286,11 @@ impl From<Element> for ElementRepr {
 pub struct ElementDAO;
 
 impl ElementDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "CREATE TABLE IF NOT EXISTS element(id INTEGER PRIMARY KEY);",
             query_args![],
         )
@@ -112,28 +298,37 @@ impl ElementDAO {
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS element;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS element;", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn new(pool: &SqlitePool) -> Result<i64, SourcetrailError> {
-        SqliteHelper::exec(pool, "INSERT INTO element(id) VALUES(NULL);", query_args![]).await
+    #[allow(clippy::new_ret_no_self)]
+    pub async fn new(conn: impl Acquire<'_, Database = Sqlite>) -> Result<i64, SourcetrailError> {
+        SqliteHelper::exec(conn, "INSERT INTO element(id) VALUES(NULL);", query_args![]).await
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM element WHERE id = ?;", query_args![&id]).await?;
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM element WHERE id = ?;", query_args![&id]).await?;
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM element;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM element;", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<ElementRepr>, SourcetrailError> {
+    pub async fn get(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<Option<ElementRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<Element>(
-            pool,
+            conn,
             "SELECT * FROM element WHERE id = ?;",
             query_args![&id],
         )
@@ -142,10 +337,12 @@ impl ElementDAO {
         Ok(result.map(ElementRepr::from))
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<ElementRepr>, SourcetrailError> {
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<ElementRepr>, SourcetrailError> {
         Ok(SqliteHelper::fetch::<Element>(
-            pool,
-            "SELECT * FROM element;",
+            conn,
+            "SELECT * FROM element ORDER BY id;",
             SqliteArguments::default(),
         )
         .await?
@@ -180,15 +377,19 @@ impl TryFrom<ElementComponent> for ElementComponentRepr {
 pub struct ElementComponentDAO;
 
 impl ElementComponentDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS element_component(id INTEGER PRIMARY KEY, element_id INTEGER, type INTEGER, data TEXT, FOREIGN KEY(element_id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS element_component(id INTEGER PRIMARY KEY, element_id INTEGER, type INTEGER, data TEXT, FOREIGN KEY(element_id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
 
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DROP TABLE IF EXISTS element_component;",
             query_args![],
         )
@@ -196,22 +397,26 @@ impl ElementComponentDAO {
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<ElementComponentRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO element_component(id, element_id, type, data) VALUES(NULL, ?, ?, ?);",
             query_args![obj.elem_id(), obj.component_type() as i32, obj.data()],
         )
         .await
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DELETE FROM element_component WHERE id = ?;",
             query_args![&id],
         )
@@ -219,17 +424,17 @@ impl ElementComponentDAO {
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM element_component;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM element_component;", query_args![]).await?;
         Ok(())
     }
 
     pub async fn get(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         id: i64,
     ) -> Result<Option<ElementComponentRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<ElementComponent>(
-            pool,
+            conn,
             "SELECT * FROM element_component WHERE id = ?;",
             query_args![&id],
         )
@@ -238,10 +443,42 @@ impl ElementComponentDAO {
         result.map(ElementComponentRepr::try_from).transpose()
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<ElementComponentRepr>, SourcetrailError> {
+    pub async fn get_by_element(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        element_id: i64,
+        type_: ElementComponentType,
+    ) -> Result<Option<ElementComponentRepr>, SourcetrailError> {
+        let result = SqliteHelper::fetch_one::<ElementComponent>(
+            conn,
+            "SELECT * FROM element_component WHERE element_id = ? AND type = ? ORDER BY id LIMIT 1;",
+            query_args![element_id, type_ as i32],
+        )
+        .await?;
+
+        result.map(ElementComponentRepr::try_from).transpose()
+    }
+
+    pub async fn list_for_element(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        element_id: i64,
+    ) -> Result<Vec<ElementComponentRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<ElementComponent>(
+            conn,
+            "SELECT * FROM element_component WHERE element_id = ? ORDER BY id;",
+            query_args![element_id],
+        )
+        .await?
+        .into_iter()
+        .map(ElementComponentRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<ElementComponentRepr>, SourcetrailError> {
         SqliteHelper::fetch::<ElementComponent>(
-            pool,
-            "SELECT * FROM element_component;",
+            conn,
+            "SELECT * FROM element_component ORDER BY id;",
             query_args![],
         )
         .await?
@@ -273,26 +510,53 @@ impl TryFrom<Edge> for EdgeRepr {
     }
 }
 
+#[derive(FromRow, Debug)]
+struct EdgeLocation {
+    edge_id: i64,
+    edge_type: i32,
+    edge_source_node_id: i64,
+    edge_target_node_id: i64,
+    #[sqlx(flatten)]
+    location: SourceLocation,
+}
+
+impl EdgeLocation {
+    fn try_into_pair(self) -> Result<(EdgeRepr, SourceLocationRepr), SourcetrailError> {
+        let edge = Edge {
+            id: self.edge_id,
+            type_: self.edge_type,
+            source_node_id: self.edge_source_node_id,
+            target_node_id: self.edge_target_node_id,
+        };
+        Ok((edge.try_into()?, self.location.try_into()?))
+    }
+}
+
 pub struct EdgeDAO;
 
 impl EdgeDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS edge(id INTEGER PRIMARY KEY, type INTEGER, source_node_id INTEGER, target_node_id INTEGER, FOREIGN KEY(source_node_id) REFERENCES node(id) ON DELETE CASCADE, FOREIGN KEY(target_node_id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS edge(id INTEGER PRIMARY KEY, type INTEGER, source_node_id INTEGER, target_node_id INTEGER, FOREIGN KEY(source_node_id) REFERENCES node(id) ON DELETE CASCADE, FOREIGN KEY(target_node_id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS edge;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS edge;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<EdgeRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO edge(id, type, source_node_id, target_node_id) VALUES(?, ?, ?, ?);",
             query_args![
                 obj.id(),
@@ -301,22 +565,42 @@ impl EdgeDAO {
                 obj.target_id()
             ],
         )
-        .await
+        .await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM edge WHERE id = ?;", query_args![&id]).await?;
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM edge WHERE id = ?;", query_args![&id]).await?;
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM edge;", query_args![]).await?;
+    pub async fn delete_for_node(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        node_id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(
+            conn,
+            "DELETE FROM element WHERE id IN (SELECT id FROM edge WHERE source_node_id = ? OR target_node_id = ?);",
+            query_args![node_id, node_id],
+        )
+        .await?;
         Ok(())
     }
 
-    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<EdgeRepr>, SourcetrailError> {
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM edge;", query_args![]).await?;
+        Ok(())
+    }
+
+    pub async fn get(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<Option<EdgeRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<Edge>(
-            pool,
+            conn,
             "SELECT * FROM edge WHERE id = ?;",
             query_args![id],
         )
@@ -325,8 +609,148 @@ impl EdgeDAO {
         result.map(EdgeRepr::try_from).transpose()
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<EdgeRepr>, SourcetrailError> {
-        SqliteHelper::fetch::<Edge>(pool, "SELECT * FROM edge;", query_args![])
+    pub async fn list_by_target(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        target_id: i64,
+        type_: EdgeType,
+    ) -> Result<Vec<EdgeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Edge>(
+            conn,
+            "SELECT * FROM edge WHERE target_node_id = ? AND type = ? ORDER BY id;",
+            query_args![target_id, type_ as i32],
+        )
+        .await?
+        .into_iter()
+        .map(EdgeRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_by_source(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        source_id: i64,
+        type_: EdgeType,
+    ) -> Result<Vec<EdgeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Edge>(
+            conn,
+            "SELECT * FROM edge WHERE source_node_id = ? AND type = ? ORDER BY id;",
+            query_args![source_id, type_ as i32],
+        )
+        .await?
+        .into_iter()
+        .map(EdgeRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_by_type(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        type_: EdgeType,
+    ) -> Result<Vec<EdgeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Edge>(
+            conn,
+            "SELECT * FROM edge WHERE type = ? ORDER BY id;",
+            query_args![type_ as i32],
+        )
+        .await?
+        .into_iter()
+        .map(EdgeRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_incoming_to_file(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        file_id: i64,
+    ) -> Result<Vec<EdgeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Edge>(
+            conn,
+            "SELECT DISTINCT edge.* FROM edge JOIN occurrence ON occurrence.element_id = edge.target_node_id JOIN source_location ON source_location.id = occurrence.source_location_id WHERE source_location.file_node_id = ? AND edge.type != ? ORDER BY edge.id;",
+            query_args![file_id, EdgeType::Member as i32],
+        )
+        .await?
+        .into_iter()
+        .map(EdgeRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    /// Edges with a `Token` or `Unsolved` occurrence in `file_id`, paired with
+    /// that location.
+    pub async fn list_with_locations_in_file(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        file_id: i64,
+    ) -> Result<Vec<(EdgeRepr, SourceLocationRepr)>, SourcetrailError> {
+        SqliteHelper::fetch::<EdgeLocation>(
+            conn,
+            "SELECT edge.id AS edge_id, edge.type AS edge_type, edge.source_node_id AS edge_source_node_id, edge.target_node_id AS edge_target_node_id, source_location.* FROM edge JOIN occurrence ON occurrence.element_id = edge.id JOIN source_location ON source_location.id = occurrence.source_location_id WHERE source_location.file_node_id = ? AND source_location.type IN (?, ?) ORDER BY source_location.start_line, source_location.start_column, edge.id;",
+            query_args![
+                file_id,
+                SourceLocationType::Token as i32,
+                SourceLocationType::Unsolved as i32
+            ],
+        )
+        .await?
+        .into_iter()
+        .map(EdgeLocation::try_into_pair)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_with_locations_by_target(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        target_id: i64,
+        type_: EdgeType,
+    ) -> Result<Vec<(EdgeRepr, SourceLocationRepr)>, SourcetrailError> {
+        SqliteHelper::fetch::<EdgeLocation>(
+            conn,
+            "SELECT edge.id AS edge_id, edge.type AS edge_type, edge.source_node_id AS edge_source_node_id, edge.target_node_id AS edge_target_node_id, source_location.* FROM edge JOIN occurrence ON occurrence.element_id = edge.id JOIN source_location ON source_location.id = occurrence.source_location_id WHERE edge.target_node_id = ? AND edge.type = ? ORDER BY edge.id, source_location.id;",
+            query_args![target_id, type_ as i32],
+        )
+        .await?
+        .into_iter()
+        .map(EdgeLocation::try_into_pair)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_with_locations_by_type(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        type_: EdgeType,
+    ) -> Result<Vec<(EdgeRepr, SourceLocationRepr)>, SourcetrailError> {
+        SqliteHelper::fetch::<EdgeLocation>(
+            conn,
+            "SELECT edge.id AS edge_id, edge.type AS edge_type, edge.source_node_id AS edge_source_node_id, edge.target_node_id AS edge_target_node_id, source_location.* FROM edge JOIN occurrence ON occurrence.element_id = edge.id JOIN source_location ON source_location.id = occurrence.source_location_id WHERE edge.type = ? ORDER BY edge.id, source_location.id;",
+            query_args![type_ as i32],
+        )
+        .await?
+        .into_iter()
+        .map(EdgeLocation::try_into_pair)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn count(conn: impl Acquire<'_, Database = Sqlite>) -> Result<i64, SourcetrailError> {
+        SqliteHelper::count(conn, "SELECT COUNT(*) FROM edge;", query_args![]).await
+    }
+
+    /// Incoming and outgoing edge counts per node, ignoring `Member` edges.
+    pub async fn degrees(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<(i64, i64, i64)>, SourcetrailError> {
+        SqliteHelper::fetch::<(i64, i64, i64)>(
+            conn,
+            "SELECT node_id, SUM(incoming), SUM(outgoing) FROM (SELECT target_node_id AS node_id, 1 AS incoming, 0 AS outgoing FROM edge WHERE type != ? UNION ALL SELECT source_node_id, 0, 1 FROM edge WHERE type != ?) GROUP BY node_id;",
+            query_args![EdgeType::Member as i32, EdgeType::Member as i32],
+        )
+        .await
+    }
+
+    pub fn stream(
+        pool: &SqlitePool,
+    ) -> impl Stream<Item = Result<EdgeRepr, SourcetrailError>> + '_ {
+        sqlx::query_as::<_, Edge>("SELECT * FROM edge ORDER BY id;")
+            .fetch(pool)
+            .map(|edge| EdgeRepr::try_from(edge?))
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<EdgeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Edge>(conn, "SELECT * FROM edge ORDER BY id;", query_args![])
             .await?
             .into_iter()
             .map(EdgeRepr::try_from)
@@ -357,42 +781,54 @@ impl TryFrom<Node> for NodeRepr {
 pub struct NodeDAO;
 
 impl NodeDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS node(id INTEGER PRIMARY KEY, type INTEGER, serialized_name TEXT, FOREIGN KEY(id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS node(id INTEGER PRIMARY KEY, type INTEGER, serialized_name TEXT, FOREIGN KEY(id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS node;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS node;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<NodeRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO node(id, type, serialized_name) VALUES(?, ?, ?);",
             query_args![obj.id(), obj.type_() as i32, obj.name()],
         )
-        .await
+        .await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM node WHERE id = ?;", query_args![&id]).await?;
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM node WHERE id = ?;", query_args![&id]).await?;
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM node;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM node;", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<NodeRepr>, SourcetrailError> {
+    pub async fn get(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<Option<NodeRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<Node>(
-            pool,
+            conn,
             "SELECT * FROM node WHERE id = ?;",
             query_args![&id],
         )
@@ -402,12 +838,12 @@ impl NodeDAO {
     }
 
     pub async fn get_by_name(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         name: impl AsRef<str>,
     ) -> Result<Option<NodeRepr>, SourcetrailError> {
         let name = name.as_ref();
         let result = SqliteHelper::fetch_one::<Node>(
-            pool,
+            conn,
             "SELECT * FROM node WHERE serialized_name = ? LIMIT 1;",
             query_args![&name],
         )
@@ -416,13 +852,41 @@ impl NodeDAO {
         result.map(NodeRepr::try_from).transpose()
     }
 
+    pub async fn list_by_names(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        names: &[String],
+    ) -> Result<Vec<NodeRepr>, SourcetrailError> {
+        use sqlx::Arguments;
+
+        let mut conn = conn.acquire().await?;
+        let mut nodes = Vec::with_capacity(names.len());
+
+        for chunk in names.chunks(SqliteHelper::MAX_VARIABLES) {
+            let query = format!(
+                "SELECT * FROM node WHERE serialized_name IN ({});",
+                vec!["?"; chunk.len()].join(", ")
+            );
+
+            let mut args = SqliteArguments::default();
+            for name in chunk {
+                args.add(name.as_str());
+            }
+
+            for node in SqliteHelper::fetch::<Node>(&mut *conn, &query, args).await? {
+                nodes.push(NodeRepr::try_from(node)?);
+            }
+        }
+
+        Ok(nodes)
+    }
+
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<NodeRepr>,
     ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "UPDATE node SET type = ?, serialized_name = ? WHERE id = ?;",
             query_args![obj.type_() as i32, obj.name(), obj.id()],
         )
@@ -430,13 +894,112 @@ impl NodeDAO {
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<NodeRepr>, SourcetrailError> {
-        SqliteHelper::fetch::<Node>(pool, "SELECT * FROM node;", query_args![])
+    pub async fn count(conn: impl Acquire<'_, Database = Sqlite>) -> Result<i64, SourcetrailError> {
+        SqliteHelper::count(conn, "SELECT COUNT(*) FROM node;", query_args![]).await
+    }
+
+    pub async fn count_by_type(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<(NodeType, i64)>, SourcetrailError> {
+        SqliteHelper::fetch::<(i32, i64)>(
+            conn,
+            "SELECT type, COUNT(*) FROM node GROUP BY type;",
+            query_args![],
+        )
+        .await?
+        .into_iter()
+        .map(|(type_, count)| Ok((type_.try_into().map_err(SourcetrailError::convert)?, count)))
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_by_type(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        type_: NodeType,
+    ) -> Result<Vec<NodeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Node>(
+            conn,
+            "SELECT * FROM node WHERE type = ? ORDER BY id;",
+            query_args![type_ as i32],
+        )
+        .await?
+        .into_iter()
+        .map(NodeRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_by_name_like(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        pattern: &str,
+    ) -> Result<Vec<NodeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Node>(
+            conn,
+            "SELECT * FROM node WHERE serialized_name LIKE ? ORDER BY id;",
+            query_args![pattern],
+        )
+        .await?
+        .into_iter()
+        .map(NodeRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_by_type_in_file(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        file_id: i64,
+        type_: NodeType,
+    ) -> Result<Vec<NodeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Node>(
+            conn,
+            "SELECT DISTINCT node.* FROM node JOIN occurrence ON occurrence.element_id = node.id JOIN source_location ON source_location.id = occurrence.source_location_id WHERE source_location.file_node_id = ? AND node.type = ? ORDER BY node.id;",
+            query_args![file_id, type_ as i32],
+        )
+        .await?
+        .into_iter()
+        .map(NodeRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub fn stream(
+        pool: &SqlitePool,
+    ) -> impl Stream<Item = Result<NodeRepr, SourcetrailError>> + '_ {
+        sqlx::query_as::<_, Node>("SELECT * FROM node ORDER BY id;")
+            .fetch(pool)
+            .map(|node| NodeRepr::try_from(node?))
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<NodeRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Node>(conn, "SELECT * FROM node ORDER BY id;", query_args![])
             .await?
             .into_iter()
             .map(NodeRepr::try_from)
             .collect::<Result<_, _>>()
     }
+
+    /// Like `list`, but skips (and logs) rows that fail to convert, returning
+    /// the number of skipped rows alongside the good ones.
+    pub async fn list_lossy(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(Vec<NodeRepr>, usize), SourcetrailError> {
+        let rows =
+            SqliteHelper::fetch::<Node>(conn, "SELECT * FROM node ORDER BY id;", query_args![])
+                .await?;
+
+        let mut nodes = Vec::with_capacity(rows.len());
+        let mut skipped = 0;
+        for row in rows {
+            let id = row.id;
+            match NodeRepr::try_from(row) {
+                Ok(node) => nodes.push(node),
+                Err(e) => {
+                    log::warn!("skipping node {id}: {e}");
+                    skipped += 1;
+                }
+            }
+        }
+
+        Ok((nodes, skipped))
+    }
 }
 
 #[derive(FromRow, Debug)]
@@ -461,43 +1024,55 @@ impl TryFrom<Symbol> for SymbolRepr {
 pub struct SymbolDAO;
 
 impl SymbolDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS symbol(id INTEGER PRIMARY KEY, definition_kind INTEGER, FOREIGN KEY(id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS symbol(id INTEGER PRIMARY KEY, definition_kind INTEGER, FOREIGN KEY(id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
 
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS symbol;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS symbol;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<SymbolRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO symbol(id, definition_kind) VALUES(?, ?);",
             query_args![obj.id(), obj.definition_kind() as i32],
         )
-        .await
+        .await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM symbol WHERE id = ?;", query_args![&id]).await?;
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM symbol WHERE id = ?;", query_args![&id]).await?;
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM symbol;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM symbol;", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<SymbolRepr>, SourcetrailError> {
+    pub async fn get(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<Option<SymbolRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<Symbol>(
-            pool,
+            conn,
             "SELECT * FROM symbol WHERE id = ?;",
             query_args![id],
         )
@@ -507,12 +1082,12 @@ impl SymbolDAO {
     }
 
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<SymbolRepr>,
     ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "UPDATE symbol SET definition_kind = ? WHERE id = ?;",
             query_args![obj.definition_kind() as i32, obj.id()],
         )
@@ -520,8 +1095,14 @@ impl SymbolDAO {
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<SymbolRepr>, SourcetrailError> {
-        SqliteHelper::fetch::<Symbol>(pool, "SELECT * FROM symbol;", query_args![])
+    pub async fn count(conn: impl Acquire<'_, Database = Sqlite>) -> Result<i64, SourcetrailError> {
+        SqliteHelper::count(conn, "SELECT COUNT(*) FROM symbol;", query_args![]).await
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<SymbolRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<Symbol>(conn, "SELECT * FROM symbol ORDER BY id;", query_args![])
             .await?
             .into_iter()
             .map(SymbolRepr::try_from)
@@ -548,7 +1129,7 @@ impl TryFrom<File> for FileRepr {
             f.id,
             f.path,
             f.language,
-            NaiveDateTime::parse_from_str("%Y-%m-%d %H:%M:%S", &f.modification_time)
+            NaiveDateTime::parse_from_str(&f.modification_time, "%Y-%m-%d %H:%M:%S")
                 .map_err(SourcetrailError::convert)?
                 .and_utc(),
             f.indexed,
@@ -558,58 +1139,186 @@ impl TryFrom<File> for FileRepr {
     }
 }
 
+#[derive(FromRow, Debug)]
+struct FileErrorCount {
+    #[sqlx(flatten)]
+    file: File,
+    error_count: i64,
+}
+
 pub struct FileDAO;
 
 impl FileDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS file(id INTEGER PRIMARY KEY, path TEXT, language TEXT, modification_time TEXT, indexed BOOLEAN, complete BOOLEAN, line_count INTEGER, FOREIGN KEY(id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS file(id INTEGER PRIMARY KEY, path TEXT, language TEXT, modification_time TEXT, indexed BOOLEAN, complete BOOLEAN, line_count INTEGER, FOREIGN KEY(id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS file;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS file;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<FileRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
-        SqliteHelper::exec(pool, "INSERT INTO file(id, path, language, modification_time, indexed, complete, line_count) VALUES(?, ?, ?, ?, ?, ?, ?);", query_args![obj.id(), obj.path().to_string_lossy(), obj.language(), obj.modification_time_str(), obj.is_indexed(), obj.is_complete(), obj.line_count()]).await
+        SqliteHelper::exec(conn, "INSERT INTO file(id, path, language, modification_time, indexed, complete, line_count) VALUES(?, ?, ?, ?, ?, ?, ?);", query_args![obj.id(), obj.path().to_string_lossy(), obj.language(), obj.modification_time_str(), obj.is_indexed(), obj.is_complete(), obj.line_count()]).await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM file WHERE id = ?;", query_args![&id]).await?;
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM file WHERE id = ?;", query_args![&id]).await?;
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM file;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM file;", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<FileRepr>, SourcetrailError> {
+    pub async fn get(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<Option<FileRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<File>(
-            pool,
+            conn,
             "SELECT * FROM file WHERE id = ?;",
             query_args![&id],
         )
-        .await?;
-        result.map(FileRepr::try_from).transpose()
+        .await?;
+        result.map(FileRepr::try_from).transpose()
+    }
+
+    pub async fn update(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        obj: impl AsRef<FileRepr>,
+    ) -> Result<(), SourcetrailError> {
+        let obj = obj.as_ref();
+        SqliteHelper::exec(conn, "UPDATE file SET path = ?, language = ?, modification_time = ?, indexed = ?, complete = ?, line_count = ? WHERE id = ?;", query_args![obj.path().to_string_lossy(), obj.language(), obj.modification_time_str(), obj.is_indexed(), obj.is_complete(), obj.line_count(), obj.id()]).await?;
+        Ok(())
+    }
+
+    pub async fn set_complete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+        complete: bool,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(
+            conn,
+            "UPDATE file SET complete = ? WHERE id = ?;",
+            query_args![complete, id],
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_by_language(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        language: impl AsRef<str>,
+    ) -> Result<Vec<FileRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<File>(
+            conn,
+            "SELECT * FROM file WHERE language = ? ORDER BY id;",
+            query_args![language.as_ref()],
+        )
+        .await?
+        .into_iter()
+        .map(FileRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_referencing_node(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        node_id: i64,
+    ) -> Result<Vec<FileRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<File>(
+            conn,
+            "SELECT DISTINCT file.* FROM file JOIN source_location ON source_location.file_node_id = file.id JOIN occurrence ON occurrence.source_location_id = source_location.id JOIN edge ON edge.id = occurrence.element_id WHERE edge.source_node_id = ? OR edge.target_node_id = ? ORDER BY file.id;",
+            query_args![node_id, node_id],
+        )
+        .await?
+        .into_iter()
+        .map(FileRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_by_line_count(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        limit: i64,
+    ) -> Result<Vec<FileRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<File>(
+            conn,
+            "SELECT * FROM file ORDER BY line_count DESC, id LIMIT ?;",
+            query_args![limit],
+        )
+        .await?
+        .into_iter()
+        .map(FileRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn list_with_error_counts(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<(FileRepr, i64)>, SourcetrailError> {
+        SqliteHelper::fetch::<FileErrorCount>(
+            conn,
+            "SELECT file.*, COUNT(DISTINCT error.id) AS error_count FROM file JOIN source_location ON source_location.file_node_id = file.id JOIN occurrence ON occurrence.source_location_id = source_location.id JOIN error ON error.id = occurrence.element_id GROUP BY file.id ORDER BY file.id;",
+            query_args![],
+        )
+        .await?
+        .into_iter()
+        .map(|row| Ok((row.file.try_into()?, row.error_count)))
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn count_by_language(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<(String, i64)>, SourcetrailError> {
+        SqliteHelper::fetch::<(String, i64)>(
+            conn,
+            "SELECT language, COUNT(*) FROM file GROUP BY language;",
+            query_args![],
+        )
+        .await
+    }
+
+    pub async fn count(conn: impl Acquire<'_, Database = Sqlite>) -> Result<i64, SourcetrailError> {
+        SqliteHelper::count(conn, "SELECT COUNT(*) FROM file;", query_args![]).await
+    }
+
+    pub async fn total_line_count(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<i64, SourcetrailError> {
+        SqliteHelper::count(
+            conn,
+            "SELECT COALESCE(SUM(line_count), 0) FROM file WHERE indexed = 1;",
+            query_args![],
+        )
+        .await
     }
 
-    pub async fn update(
+    pub fn stream(
         pool: &SqlitePool,
-        obj: impl AsRef<FileRepr>,
-    ) -> Result<(), SourcetrailError> {
-        let obj = obj.as_ref();
-        SqliteHelper::exec(pool, "UPDATE file SET path = ?, language = ?, modification_time = ?, indexed = ?, complete = ?, line_count = ? WHERE id = ?;", query_args![obj.path().to_string_lossy(), obj.language(), obj.modification_time().to_rfc3339(), obj.is_indexed(), obj.is_complete(), obj.line_count(), obj.id()]).await?;
-        Ok(())
+    ) -> impl Stream<Item = Result<FileRepr, SourcetrailError>> + '_ {
+        sqlx::query_as::<_, File>("SELECT * FROM file ORDER BY id;")
+            .fetch(pool)
+            .map(|file| FileRepr::try_from(file?))
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<FileRepr>, SourcetrailError> {
-        SqliteHelper::fetch::<File>(pool, "SELECT * FROM file;", query_args![])
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<FileRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<File>(conn, "SELECT * FROM file ORDER BY id;", query_args![])
             .await?
             .into_iter()
             .map(FileRepr::try_from)
@@ -632,32 +1341,41 @@ impl From<FileContent> for FileContentRepr {
 pub struct FileContentDAO;
 
 impl FileContentDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS filecontent(id INTEGER PRIMARY KEY, content TEXT, FOREIGN KEY(id) REFERENCES file(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS filecontent(id INTEGER PRIMARY KEY, content TEXT, FOREIGN KEY(id) REFERENCES file(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS filecontent;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS filecontent;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<FileContentRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO filecontent(id, content) VALUES(?, ?);",
             query_args![obj.id(), obj.content()],
         )
-        .await
+        .await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DELETE FROM filecontent WHERE id = ?;",
             query_args![&id],
         )
@@ -665,17 +1383,17 @@ impl FileContentDAO {
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM filecontent;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM filecontent;", query_args![]).await?;
         Ok(())
     }
 
     pub async fn get(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         id: i64,
     ) -> Result<Option<FileContentRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<FileContent>(
-            pool,
+            conn,
             "SELECT * FROM filecontent WHERE id = ?;",
             query_args![&id],
         )
@@ -685,12 +1403,12 @@ impl FileContentDAO {
     }
 
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<FileContentRepr>,
     ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "UPDATE filecontent SET content = ? WHERE id = ?;",
             query_args![obj.content(), obj.id()],
         )
@@ -698,14 +1416,18 @@ impl FileContentDAO {
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<FileContentRepr>, SourcetrailError> {
-        Ok(
-            SqliteHelper::fetch::<FileContent>(pool, "SELECT * FROM filecontent;", query_args![])
-                .await?
-                .into_iter()
-                .map(FileContentRepr::from)
-                .collect(),
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<FileContentRepr>, SourcetrailError> {
+        Ok(SqliteHelper::fetch::<FileContent>(
+            conn,
+            "SELECT * FROM filecontent ORDER BY id;",
+            query_args![],
         )
+        .await?
+        .into_iter()
+        .map(FileContentRepr::from)
+        .collect())
     }
 }
 
@@ -724,32 +1446,41 @@ impl From<LocalSymbol> for LocalSymbolRepr {
 pub struct LocalSymbolDAO;
 
 impl LocalSymbolDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS local_symbol(id INTEGER PRIMARY KEY, name TEXT, FOREIGN KEY(id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS local_symbol(id INTEGER PRIMARY KEY, name TEXT, FOREIGN KEY(id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS local_symbol;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS local_symbol;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<LocalSymbolRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO local_symbol(id, name) VALUES(?, ?);",
             query_args![obj.id(), obj.name()],
         )
-        .await
+        .await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DELETE FROM local_symbol WHERE id = ?;",
             query_args![id],
         )
@@ -757,17 +1488,17 @@ impl LocalSymbolDAO {
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM local_symbol;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM local_symbol;", query_args![]).await?;
         Ok(())
     }
 
     pub async fn get(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         id: i64,
     ) -> Result<Option<LocalSymbolRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<LocalSymbol>(
-            pool,
+            conn,
             "SELECT * FROM local_symbol WHERE id = ?;",
             query_args![&id],
         )
@@ -777,12 +1508,12 @@ impl LocalSymbolDAO {
     }
 
     pub async fn get_by_name(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         name: impl AsRef<str>,
     ) -> Result<Option<LocalSymbolRepr>, SourcetrailError> {
         let name = name.as_ref();
         let result = SqliteHelper::fetch_one::<LocalSymbol>(
-            pool,
+            conn,
             "SELECT * FROM local_symbol WHERE name = ? LIMIT 1;",
             query_args![&name],
         )
@@ -791,12 +1522,12 @@ impl LocalSymbolDAO {
     }
 
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<LocalSymbolRepr>,
     ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "UPDATE local_symbol SET name = ? WHERE id = ?;",
             query_args![obj.name(), obj.id()],
         )
@@ -804,14 +1535,33 @@ impl LocalSymbolDAO {
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<LocalSymbolRepr>, SourcetrailError> {
-        Ok(
-            SqliteHelper::fetch::<LocalSymbol>(pool, "SELECT * FROM local_symbol;", query_args![])
-                .await?
-                .into_iter()
-                .map(LocalSymbolRepr::from)
-                .collect(),
+    pub async fn list_by_file(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        file_id: i64,
+    ) -> Result<Vec<LocalSymbolRepr>, SourcetrailError> {
+        Ok(SqliteHelper::fetch::<LocalSymbol>(
+            conn,
+            "SELECT DISTINCT local_symbol.* FROM local_symbol JOIN occurrence ON occurrence.element_id = local_symbol.id JOIN source_location ON source_location.id = occurrence.source_location_id WHERE source_location.file_node_id = ? ORDER BY local_symbol.id;",
+            query_args![file_id],
         )
+        .await?
+        .into_iter()
+        .map(LocalSymbolRepr::from)
+        .collect())
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<LocalSymbolRepr>, SourcetrailError> {
+        Ok(SqliteHelper::fetch::<LocalSymbol>(
+            conn,
+            "SELECT * FROM local_symbol ORDER BY id;",
+            query_args![],
+        )
+        .await?
+        .into_iter()
+        .map(LocalSymbolRepr::from)
+        .collect())
     }
 }
 
@@ -823,6 +1573,7 @@ struct SourceLocation {
     start_column: i32,
     end_line: i32,
     end_column: i32,
+    #[sqlx(rename = "type")]
     type_: i32,
 }
 
@@ -842,30 +1593,47 @@ impl TryFrom<SourceLocation> for SourceLocationRepr {
     }
 }
 
+#[derive(FromRow, Debug)]
+struct NodeLocation {
+    node_id: i64,
+    node_type: i32,
+    node_serialized_name: String,
+    #[sqlx(flatten)]
+    location: SourceLocation,
+}
+
 pub struct SourceLocationDAO;
 
 impl SourceLocationDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS source_location(id INTEGER PRIMARY KEY, file_node_id INTEGER, start_line INTEGER, start_column INTEGER, end_line INTEGER, end_column INTEGER, type INTEGER, FOREIGN KEY(file_node_id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS source_location(id INTEGER PRIMARY KEY, file_node_id INTEGER, start_line INTEGER, start_column INTEGER, end_line INTEGER, end_column INTEGER, type INTEGER, FOREIGN KEY(file_node_id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS source_location;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS source_location;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<SourceLocationRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
-        SqliteHelper::exec(pool, "INSERT INTO source_location(id, file_node_id, start_line, start_column, end_line, end_column, type) VALUES(NULL, ?, ?, ?, ?, ?, ?);", query_args![obj.file_node_id(), obj.start_line(), obj.start_column(), obj.end_line(), obj.end_column(), obj.location_type() as i32]).await
+        SqliteHelper::exec(conn, "INSERT INTO source_location(id, file_node_id, start_line, start_column, end_line, end_column, type) VALUES(NULL, ?, ?, ?, ?, ?, ?);", query_args![obj.file_node_id(), obj.start_line(), obj.start_column(), obj.end_line(), obj.end_column(), obj.location_type() as i32]).await
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DELETE FROM source_location WHERE id = ?;",
             query_args![&id],
         )
@@ -873,17 +1641,29 @@ impl SourceLocationDAO {
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM source_location;", query_args![]).await?;
+    pub async fn delete_unreferenced(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(
+            conn,
+            "DELETE FROM source_location WHERE id NOT IN (SELECT source_location_id FROM occurrence);",
+            query_args![],
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM source_location;", query_args![]).await?;
         Ok(())
     }
 
     pub async fn get(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         id: i64,
     ) -> Result<Option<SourceLocationRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<SourceLocation>(
-            pool,
+            conn,
             "SELECT * FROM source_location WHERE id = ?;",
             query_args![id],
         )
@@ -893,20 +1673,101 @@ impl SourceLocationDAO {
     }
 
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<SourceLocationRepr>,
     ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
-        SqliteHelper::exec(pool, "UPDATE source_location SET file_node_id = ?, start_line = ?, start_column = ?, end_line = ?, end_column = ?, type = ? WHERE id = ?;", query_args![obj.file_node_id(), obj.start_line(), obj.start_column(), obj.end_line(), obj.end_column(), obj.location_type() as i32, obj.id()]).await?;
+        SqliteHelper::exec(conn, "UPDATE source_location SET file_node_id = ?, start_line = ?, start_column = ?, end_line = ?, end_column = ?, type = ? WHERE id = ?;", query_args![obj.file_node_id(), obj.start_line(), obj.start_column(), obj.end_line(), obj.end_column(), obj.location_type() as i32, obj.id()]).await?;
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<SourceLocationRepr>, SourcetrailError> {
-        SqliteHelper::fetch::<SourceLocation>(pool, "SELECT * FROM source_location;", query_args![])
-            .await?
-            .into_iter()
-            .map(SourceLocationRepr::try_from)
-            .collect::<Result<_, _>>()
+    pub async fn list_by_element(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        element_id: i64,
+    ) -> Result<Vec<SourceLocationRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<SourceLocation>(
+            conn,
+            "SELECT source_location.* FROM source_location JOIN occurrence ON source_location.id = occurrence.source_location_id WHERE occurrence.element_id = ? ORDER BY source_location.id;",
+            query_args![element_id],
+        )
+        .await?
+        .into_iter()
+        .map(SourceLocationRepr::try_from)
+        .collect::<Result<_, _>>()
+    }
+
+    pub async fn get_by_element(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        element_id: i64,
+        type_: SourceLocationType,
+    ) -> Result<Option<SourceLocationRepr>, SourcetrailError> {
+        let result = SqliteHelper::fetch_one::<SourceLocation>(
+            conn,
+            "SELECT source_location.* FROM source_location JOIN occurrence ON source_location.id = occurrence.source_location_id WHERE occurrence.element_id = ? AND source_location.type = ? ORDER BY source_location.id LIMIT 1;",
+            query_args![element_id, type_ as i32],
+        )
+        .await?;
+
+        result.map(SourceLocationRepr::try_from).transpose()
+    }
+
+    pub async fn list_file_ids_by_element(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        element_id: i64,
+    ) -> Result<Vec<i64>, SourcetrailError> {
+        Ok(SqliteHelper::fetch::<(i64,)>(
+            conn,
+            "SELECT DISTINCT source_location.file_node_id FROM source_location JOIN occurrence ON source_location.id = occurrence.source_location_id WHERE occurrence.element_id = ? ORDER BY source_location.file_node_id;",
+            query_args![element_id],
+        )
+        .await?
+        .into_iter()
+        .map(|(id,)| id)
+        .collect())
+    }
+
+    pub async fn list_node_locations_in_file(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        file_id: i64,
+    ) -> Result<Vec<(NodeRepr, SourceLocationRepr)>, SourcetrailError> {
+        SqliteHelper::fetch::<NodeLocation>(
+            conn,
+            "SELECT node.id AS node_id, node.type AS node_type, node.serialized_name AS node_serialized_name, source_location.* FROM source_location JOIN occurrence ON source_location.id = occurrence.source_location_id JOIN node ON node.id = occurrence.element_id WHERE source_location.file_node_id = ? ORDER BY source_location.id, node.id;",
+            query_args![file_id],
+        )
+        .await?
+        .into_iter()
+        .map(|row| {
+            let node = Node {
+                id: row.node_id,
+                type_: row.node_type,
+                serialized_name: row.node_serialized_name,
+            };
+            Ok((node.try_into()?, row.location.try_into()?))
+        })
+        .collect::<Result<_, _>>()
+    }
+
+    pub fn stream(
+        pool: &SqlitePool,
+    ) -> impl Stream<Item = Result<SourceLocationRepr, SourcetrailError>> + '_ {
+        sqlx::query_as::<_, SourceLocation>("SELECT * FROM source_location ORDER BY id;")
+            .fetch(pool)
+            .map(|loc| SourceLocationRepr::try_from(loc?))
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<SourceLocationRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<SourceLocation>(
+            conn,
+            "SELECT * FROM source_location ORDER BY id;",
+            query_args![],
+        )
+        .await?
+        .into_iter()
+        .map(SourceLocationRepr::try_from)
+        .collect::<Result<_, _>>()
     }
 }
 
@@ -925,32 +1786,54 @@ impl From<Occurrence> for OccurrenceRepr {
 pub struct OccurrenceDAO;
 
 impl OccurrenceDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS occurrence(element_id INTEGER, source_location_id INTEGER, PRIMARY KEY(element_id, source_location_id), FOREIGN KEY(element_id) REFERENCES element(id) ON DELETE CASCADE, FOREIGN KEY(source_location_id) REFERENCES source_location(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        let mut conn = conn.acquire().await?;
+        SqliteHelper::exec(&mut *conn, "CREATE TABLE IF NOT EXISTS occurrence(element_id INTEGER, source_location_id INTEGER, PRIMARY KEY(element_id, source_location_id), FOREIGN KEY(element_id) REFERENCES element(id) ON DELETE CASCADE, FOREIGN KEY(source_location_id) REFERENCES source_location(id) ON DELETE CASCADE);", query_args![]).await?;
+        SqliteHelper::exec(
+            &mut *conn,
+            "CREATE INDEX IF NOT EXISTS occurrence_element_id_index ON occurrence(element_id);",
+            query_args![],
+        )
+        .await?;
+        SqliteHelper::exec(
+            &mut *conn,
+            "CREATE INDEX IF NOT EXISTS occurrence_source_location_id_index ON occurrence(source_location_id);",
+            query_args![],
+        )
+        .await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS occurrence;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS occurrence;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<OccurrenceRepr>,
-    ) -> Result<i64, SourcetrailError> {
+    ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO occurrence(element_id, source_location_id) VALUES(?, ?);",
             query_args![obj.element_id(), obj.source_location_id()],
         )
-        .await
+        .await?;
+        Ok(())
     }
 
-    pub async fn delete(pool: &SqlitePool, element_id: i64) -> Result<(), SourcetrailError> {
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        element_id: i64,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DELETE FROM occurrence WHERE element_id = ?;",
             query_args![&element_id],
         )
@@ -958,17 +1841,17 @@ impl OccurrenceDAO {
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM occurrence;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM occurrence;", query_args![]).await?;
         Ok(())
     }
 
     pub async fn get(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         element_id: i64,
     ) -> Result<Option<OccurrenceRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<Occurrence>(
-            pool,
+            conn,
             "SELECT * FROM occurrence WHERE element_id = ?;",
             query_args![&element_id],
         )
@@ -977,13 +1860,43 @@ impl OccurrenceDAO {
         Ok(result.map(OccurrenceRepr::from))
     }
 
+    pub async fn list_by_element(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        element_id: i64,
+    ) -> Result<Vec<OccurrenceRepr>, SourcetrailError> {
+        Ok(SqliteHelper::fetch::<Occurrence>(
+            conn,
+            "SELECT * FROM occurrence WHERE element_id = ? ORDER BY source_location_id;",
+            query_args![element_id],
+        )
+        .await?
+        .into_iter()
+        .map(OccurrenceRepr::from)
+        .collect())
+    }
+
+    pub async fn list_by_location(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        source_location_id: i64,
+    ) -> Result<Vec<OccurrenceRepr>, SourcetrailError> {
+        Ok(SqliteHelper::fetch::<Occurrence>(
+            conn,
+            "SELECT * FROM occurrence WHERE source_location_id = ? ORDER BY element_id;",
+            query_args![source_location_id],
+        )
+        .await?
+        .into_iter()
+        .map(OccurrenceRepr::from)
+        .collect())
+    }
+
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<OccurrenceRepr>,
     ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "UPDATE occurrence SET source_location_id = ? WHERE element_id = ?;",
             query_args![obj.source_location_id(), obj.element_id()],
         )
@@ -991,34 +1904,54 @@ impl OccurrenceDAO {
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<OccurrenceRepr>, SourcetrailError> {
-        Ok(
-            SqliteHelper::fetch::<Occurrence>(pool, "SELECT * FROM occurrence;", query_args![])
-                .await?
-                .into_iter()
-                .map(OccurrenceRepr::from)
-                .collect(),
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<OccurrenceRepr>, SourcetrailError> {
+        Ok(SqliteHelper::fetch::<Occurrence>(
+            conn,
+            "SELECT * FROM occurrence ORDER BY element_id, source_location_id;",
+            query_args![],
         )
+        .await?
+        .into_iter()
+        .map(OccurrenceRepr::from)
+        .collect())
     }
 }
 
 #[derive(FromRow, Debug)]
-pub struct ComponentAccess {
+struct ComponentAccess {
     node_id: i64,
+    #[sqlx(rename = "type")]
     type_: i32,
 }
 
+impl TryFrom<ComponentAccess> for ComponentAccessRepr {
+    type Error = SourcetrailError;
+
+    fn try_from(access: ComponentAccess) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            access.node_id,
+            access.type_.try_into().map_err(SourcetrailError::convert)?,
+        ))
+    }
+}
+
 pub struct ComponentAccessDAO;
 
 impl ComponentAccessDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS component_access(node_id INTEGER PRIMARY KEY, type INTEGER, FOREIGN KEY(node_id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS component_access(node_id INTEGER PRIMARY KEY, type INTEGER, FOREIGN KEY(node_id) REFERENCES node(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DROP TABLE IF EXISTS component_access;",
             query_args![],
         )
@@ -1026,18 +1959,27 @@ impl ComponentAccessDAO {
         Ok(())
     }
 
-    pub async fn new(pool: &SqlitePool, obj: &ComponentAccess) -> Result<i64, SourcetrailError> {
+    #[allow(clippy::new_ret_no_self)]
+    pub async fn new(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        obj: impl AsRef<ComponentAccessRepr>,
+    ) -> Result<i64, SourcetrailError> {
+        let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO component_access(node_id, type) VALUES(?, ?);",
-            query_args![&obj.node_id, &obj.type_],
+            query_args![obj.id(), obj.access_type() as i32],
         )
-        .await
+        .await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, node_id: i64) -> Result<(), SourcetrailError> {
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        node_id: i64,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "DELETE FROM component_access WHERE node_id = ?;",
             query_args![&node_id],
         )
@@ -1045,35 +1987,51 @@ impl ComponentAccessDAO {
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM component_access;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM component_access;", query_args![]).await?;
         Ok(())
     }
 
     pub async fn get(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         node_id: i64,
-    ) -> Result<Option<ComponentAccess>, SourcetrailError> {
-        SqliteHelper::fetch_one(
-            pool,
+    ) -> Result<Option<ComponentAccessRepr>, SourcetrailError> {
+        let result = SqliteHelper::fetch_one::<ComponentAccess>(
+            conn,
             "SELECT * FROM component_access WHERE node_id = ?;",
             query_args![&node_id],
         )
-        .await
+        .await?;
+
+        result.map(ComponentAccessRepr::try_from).transpose()
     }
 
-    pub async fn update(pool: &SqlitePool, obj: &ComponentAccess) -> Result<(), SourcetrailError> {
+    pub async fn update(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        obj: impl AsRef<ComponentAccessRepr>,
+    ) -> Result<(), SourcetrailError> {
+        let obj = obj.as_ref();
         SqliteHelper::exec(
-            pool,
+            conn,
             "UPDATE component_access SET type = ? WHERE node_id = ?;",
-            query_args![&obj.type_, &obj.node_id],
+            query_args![obj.access_type() as i32, obj.id()],
         )
         .await?;
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<ComponentAccess>, SourcetrailError> {
-        SqliteHelper::fetch(pool, "SELECT * FROM component_access;", query_args![]).await
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<ComponentAccessRepr>, SourcetrailError> {
+        SqliteHelper::fetch::<ComponentAccess>(
+            conn,
+            "SELECT * FROM component_access ORDER BY node_id;",
+            query_args![],
+        )
+        .await?
+        .into_iter()
+        .map(ComponentAccessRepr::try_from)
+        .collect::<Result<_, _>>()
     }
 }
 
@@ -1098,40 +2056,64 @@ impl From<Error> for ErrorRepr {
     }
 }
 
+#[derive(FromRow, Debug)]
+struct ErrorLocation {
+    error_id: i64,
+    error_message: String,
+    error_fatal: bool,
+    error_indexed: bool,
+    error_translation_unit: String,
+    file_path: String,
+    #[sqlx(flatten)]
+    location: SourceLocation,
+}
+
 pub struct ErrorDAO;
 
 impl ErrorDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "CREATE TABLE IF NOT EXISTS error(id INTEGER PRIMARY KEY, message TEXT, fatal BOOLEAN, indexed BOOLEAN, translation_unit TEXT, FOREIGN KEY(id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "CREATE TABLE IF NOT EXISTS error(id INTEGER PRIMARY KEY, message TEXT, fatal BOOLEAN, indexed BOOLEAN, translation_unit TEXT, FOREIGN KEY(id) REFERENCES element(id) ON DELETE CASCADE);", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS error;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS error;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<ErrorRepr>,
     ) -> Result<i64, SourcetrailError> {
         let obj = obj.as_ref();
-        SqliteHelper::exec(pool, "INSERT INTO error(id, message, fatal, indexed, translation_unit) VALUES(?, ?, ?, ?, ?);", query_args![obj.id(), obj.message(), obj.is_fatal(), obj.is_indexed(), obj.translation_unit()]).await
+        SqliteHelper::exec(conn, "INSERT INTO error(id, message, fatal, indexed, translation_unit) VALUES(?, ?, ?, ?, ?);", query_args![obj.id(), obj.message(), obj.is_fatal(), obj.is_indexed(), obj.translation_unit()]).await?;
+        Ok(obj.id())
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM error WHERE id = ?;", query_args![&id]).await?;
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM error WHERE id = ?;", query_args![&id]).await?;
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM error;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM error;", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<ErrorRepr>, SourcetrailError> {
+    pub async fn get(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<Option<ErrorRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<Error>(
-            pool,
+            conn,
             "SELECT * FROM error WHERE id = ?;",
             query_args![id],
         )
@@ -1141,17 +2123,50 @@ impl ErrorDAO {
     }
 
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         obj: impl AsRef<ErrorRepr>,
     ) -> Result<(), SourcetrailError> {
         let obj = obj.as_ref();
-        SqliteHelper::exec(pool, "UPDATE error SET message = ?, fatal = ?, indexed = ?, translation_unit = ? WHERE id = ?;", query_args![obj.message(), obj.is_fatal(), obj.is_indexed(), obj.translation_unit(), obj.id()]).await?;
+        SqliteHelper::exec(conn, "UPDATE error SET message = ?, fatal = ?, indexed = ?, translation_unit = ? WHERE id = ?;", query_args![obj.message(), obj.is_fatal(), obj.is_indexed(), obj.translation_unit(), obj.id()]).await?;
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<ErrorRepr>, SourcetrailError> {
+    pub async fn list_with_locations(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<(ErrorRepr, String, SourceLocationRepr)>, SourcetrailError> {
+        SqliteHelper::fetch::<ErrorLocation>(
+            conn,
+            "SELECT error.id AS error_id, error.message AS error_message, error.fatal AS error_fatal, error.indexed AS error_indexed, error.translation_unit AS error_translation_unit, file.path AS file_path, source_location.* FROM error JOIN occurrence ON occurrence.element_id = error.id JOIN source_location ON source_location.id = occurrence.source_location_id JOIN file ON file.id = source_location.file_node_id WHERE source_location.type = ? ORDER BY file.path, source_location.start_line, source_location.start_column, error.id;",
+            query_args![SourceLocationType::IndexerError as i32],
+        )
+        .await?
+        .into_iter()
+        .map(|row| {
+            let error = ErrorRepr::new(
+                row.error_id,
+                row.error_message,
+                row.error_fatal,
+                row.error_indexed,
+                row.error_translation_unit,
+            );
+            Ok((error, row.file_path, row.location.try_into()?))
+        })
+        .collect::<Result<_, _>>()
+    }
+
+    pub fn stream(
+        pool: &SqlitePool,
+    ) -> impl Stream<Item = Result<ErrorRepr, SourcetrailError>> + '_ {
+        sqlx::query_as::<_, Error>("SELECT * FROM error ORDER BY id;")
+            .fetch(pool)
+            .map(|error| Ok(ErrorRepr::from(error?)))
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<ErrorRepr>, SourcetrailError> {
         Ok(
-            SqliteHelper::fetch::<Error>(pool, "SELECT * FROM error;", query_args![])
+            SqliteHelper::fetch::<Error>(conn, "SELECT * FROM error ORDER BY id;", query_args![])
                 .await?
                 .into_iter()
                 .map(ErrorRepr::from)
@@ -1176,9 +2191,11 @@ impl From<Meta> for MetaRepr {
 pub struct MetaDAO;
 
 impl MetaDAO {
-    pub async fn create_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
+    pub async fn create_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "CREATE TABLE IF NOT EXISTS meta(id INTEGER PRIMARY KEY, key TEXT, value TEXT);",
             query_args![],
         )
@@ -1186,37 +2203,46 @@ impl MetaDAO {
         Ok(())
     }
 
-    pub async fn delete_table(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DROP TABLE IF EXISTS meta;", query_args![]).await?;
+    pub async fn delete_table(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DROP TABLE IF EXISTS meta;", query_args![]).await?;
         Ok(())
     }
 
+    #[allow(clippy::new_ret_no_self)]
     pub async fn new(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         key: impl AsRef<str>,
         value: impl AsRef<str>,
     ) -> Result<i64, SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "INSERT INTO meta(id, key, value) VALUES(NULL, ?, ?);",
             query_args![key.as_ref(), value.as_ref()],
         )
         .await
     }
 
-    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM meta WHERE id = ?;", query_args![&id]).await?;
+    pub async fn delete(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM meta WHERE id = ?;", query_args![&id]).await?;
         Ok(())
     }
 
-    pub async fn clear(pool: &SqlitePool) -> Result<(), SourcetrailError> {
-        SqliteHelper::exec(pool, "DELETE FROM meta;", query_args![]).await?;
+    pub async fn clear(conn: impl Acquire<'_, Database = Sqlite>) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(conn, "DELETE FROM meta;", query_args![]).await?;
         Ok(())
     }
 
-    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<MetaRepr>, SourcetrailError> {
+    pub async fn get(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        id: i64,
+    ) -> Result<Option<MetaRepr>, SourcetrailError> {
         let result = SqliteHelper::fetch_one::<Meta>(
-            pool,
+            conn,
             "SELECT * FROM meta WHERE id = ?;",
             query_args![&id],
         )
@@ -1225,14 +2251,42 @@ impl MetaDAO {
         Ok(result.map(MetaRepr::from))
     }
 
+    pub async fn get_by_key(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        key: impl AsRef<str>,
+    ) -> Result<Option<MetaRepr>, SourcetrailError> {
+        let result = SqliteHelper::fetch_one::<Meta>(
+            conn,
+            "SELECT * FROM meta WHERE key = ? LIMIT 1;",
+            query_args![key.as_ref()],
+        )
+        .await?;
+
+        Ok(result.map(MetaRepr::from))
+    }
+
+    pub async fn upsert(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        key: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> Result<i64, SourcetrailError> {
+        let key = key.as_ref();
+        SqliteHelper::exec(
+            conn,
+            "INSERT OR REPLACE INTO meta(id, key, value) VALUES((SELECT id FROM meta WHERE key = ? LIMIT 1), ?, ?);",
+            query_args![key, key, value.as_ref()],
+        )
+        .await
+    }
+
     pub async fn update(
-        pool: &SqlitePool,
+        conn: impl Acquire<'_, Database = Sqlite>,
         id: i64,
         key: impl AsRef<str>,
         value: impl AsRef<str>,
     ) -> Result<(), SourcetrailError> {
         SqliteHelper::exec(
-            pool,
+            conn,
             "UPDATE meta SET key = ?, value = ? WHERE id = ?;",
             query_args![key.as_ref(), value.as_ref(), id],
         )
@@ -1240,9 +2294,25 @@ impl MetaDAO {
         Ok(())
     }
 
-    pub async fn list(pool: &SqlitePool) -> Result<Vec<MetaRepr>, SourcetrailError> {
+    pub async fn update_by_key(
+        conn: impl Acquire<'_, Database = Sqlite>,
+        key: impl AsRef<str>,
+        value: impl AsRef<str>,
+    ) -> Result<(), SourcetrailError> {
+        SqliteHelper::exec(
+            conn,
+            "UPDATE meta SET value = ? WHERE key = ?;",
+            query_args![value.as_ref(), key.as_ref()],
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list(
+        conn: impl Acquire<'_, Database = Sqlite>,
+    ) -> Result<Vec<MetaRepr>, SourcetrailError> {
         Ok(
-            SqliteHelper::fetch::<Meta>(pool, "SELECT * FROM meta;", query_args![])
+            SqliteHelper::fetch::<Meta>(conn, "SELECT * FROM meta ORDER BY id;", query_args![])
                 .await?
                 .into_iter()
                 .map(MetaRepr::from)
@@ -1254,6 +2324,7 @@ impl MetaDAO {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::types::{NodeType, SymbolType};
 
     #[async_std::test]
     #[ignore]
@@ -1278,4 +2349,63 @@ mod test {
 
         Ok(())
     }
+
+    #[async_std::test]
+    async fn test_list_order() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let pool = SqliteHelper::connect(&dir.path().join("test.db").to_string_lossy()).await?;
+
+        ElementDAO::create_table(&pool).await?;
+        let first = ElementDAO::new(&pool).await?;
+        let second = ElementDAO::new(&pool).await?;
+        let third = ElementDAO::new(&pool).await?;
+
+        ElementDAO::delete(&pool, second).await?;
+        let fourth = ElementDAO::new(&pool).await?;
+
+        let ids = ElementDAO::list(&pool)
+            .await?
+            .iter()
+            .map(|e| e.id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [first, third, fourth]);
+
+        let again = ElementDAO::list(&pool)
+            .await?
+            .iter()
+            .map(|e| e.id())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, again);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_new_returns_explicit_id() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let pool = SqliteHelper::connect(&dir.path().join("test.db").to_string_lossy()).await?;
+
+        ElementDAO::create_table(&pool).await?;
+        NodeDAO::create_table(&pool).await?;
+        SymbolDAO::create_table(&pool).await?;
+        EdgeDAO::create_table(&pool).await?;
+
+        for _ in 0..3 {
+            ElementDAO::new(&pool).await?;
+        }
+
+        let node_id = NodeDAO::new(&pool, NodeRepr::new(2, NodeType::NodeClass, "A")).await?;
+        assert_eq!(node_id, 2);
+
+        let other_id = NodeDAO::new(&pool, NodeRepr::new(1, NodeType::NodeClass, "B")).await?;
+        assert_eq!(other_id, 1);
+
+        let symbol_id = SymbolDAO::new(&pool, SymbolRepr::new(2, SymbolType::Explicit)).await?;
+        assert_eq!(symbol_id, 2);
+
+        let edge_id = EdgeDAO::new(&pool, EdgeRepr::new(3, EdgeType::Usage, 2, 1)).await?;
+        assert_eq!(edge_id, 3);
+
+        Ok(())
+    }
 }
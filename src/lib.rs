@@ -1,11 +1,20 @@
 pub mod api;
+pub mod lsp;
+pub mod project;
 pub mod types;
 
 pub(crate) mod db;
 
-pub use api::{SourcetrailDB, SourcetrailError};
+pub use api::{
+    DbStats, DbTransaction, JournalMode, NodeHandle, OpenOptions, SourcetrailDB, SourcetrailError,
+    Synchronous, TypeHierarchyNode,
+};
 
 pub mod prelude {
-    pub use crate::api::{SourcetrailDB, SourcetrailError};
+    pub use crate::api::{
+        DbStats, DbTransaction, JournalMode, NodeHandle, OpenOptions, SourcetrailDB,
+        SourcetrailError, Synchronous, TypeHierarchyNode,
+    };
+    pub use crate::project::{ProjectSettings, SourceGroup};
     pub use crate::types::*;
 }
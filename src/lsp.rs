@@ -0,0 +1,192 @@
+use crate::types::{NodeType, SourceLocation};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(i32)]
+pub enum SymbolKind {
+    File = 1,
+    Module = 2,
+    Namespace = 3,
+    Package = 4,
+    Class = 5,
+    Method = 6,
+    Property = 7,
+    Field = 8,
+    Constructor = 9,
+    Enum = 10,
+    Interface = 11,
+    Function = 12,
+    Variable = 13,
+    Constant = 14,
+    String = 15,
+    Number = 16,
+    Boolean = 17,
+    Array = 18,
+    Object = 19,
+    Key = 20,
+    Null = 21,
+    EnumMember = 22,
+    Struct = 23,
+    Event = 24,
+    Operator = 25,
+    TypeParameter = 26,
+}
+
+impl From<NodeType> for SymbolKind {
+    fn from(kind: NodeType) -> Self {
+        match kind {
+            NodeType::NodeSymbol => SymbolKind::Object,
+            NodeType::NodeType | NodeType::NodeBuiltinType | NodeType::NodeTypedef => {
+                SymbolKind::Class
+            }
+            NodeType::NodeModule => SymbolKind::Module,
+            NodeType::NodeNamespace => SymbolKind::Namespace,
+            NodeType::NodePackage => SymbolKind::Package,
+            NodeType::NodeStruct | NodeType::NodeUnion => SymbolKind::Struct,
+            NodeType::NodeClass => SymbolKind::Class,
+            NodeType::NodeInterface | NodeType::NodeAnnotation => SymbolKind::Interface,
+            NodeType::NodeGlobalVariable => SymbolKind::Variable,
+            NodeType::NodeField => SymbolKind::Field,
+            NodeType::NodeFunction => SymbolKind::Function,
+            NodeType::NodeMethod => SymbolKind::Method,
+            NodeType::NodeEnum => SymbolKind::Enum,
+            NodeType::NodeEnumConstant => SymbolKind::EnumMember,
+            NodeType::NodeTypeParameter => SymbolKind::TypeParameter,
+            NodeType::NodeFile => SymbolKind::File,
+            NodeType::NodeMacro => SymbolKind::Constant,
+        }
+    }
+}
+
+/// Zero-based position, as used by LSP.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    line: u32,
+    character: u32,
+}
+
+impl Position {
+    pub fn new(line: u32, character: u32) -> Self {
+        Position { line, character }
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn character(&self) -> u32 {
+        self.character
+    }
+}
+
+/// Half-open range, as used by LSP.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Range {
+    start: Position,
+    end: Position,
+}
+
+impl Range {
+    pub fn new(start: Position, end: Position) -> Self {
+        Range { start, end }
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn end(&self) -> Position {
+        self.end
+    }
+
+    pub fn contains(&self, other: &Range) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+impl From<&SourceLocation> for Range {
+    // Sourcetrail locations are one-based with an inclusive end column.
+    fn from(loc: &SourceLocation) -> Self {
+        Range::new(
+            Position::new(
+                loc.start_line().saturating_sub(1) as u32,
+                loc.start_column().saturating_sub(1) as u32,
+            ),
+            Position::new(
+                loc.end_line().saturating_sub(1) as u32,
+                loc.end_column().max(0) as u32,
+            ),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DocumentSymbol {
+    name: String,
+    kind: SymbolKind,
+    range: Range,
+    selection_range: Range,
+    children: Vec<DocumentSymbol>,
+}
+
+impl DocumentSymbol {
+    pub fn new(
+        name: impl Into<String>,
+        kind: SymbolKind,
+        range: Range,
+        selection_range: Range,
+    ) -> Self {
+        DocumentSymbol {
+            name: name.into(),
+            kind,
+            range,
+            selection_range,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    pub fn selection_range(&self) -> Range {
+        self.selection_range
+    }
+
+    pub fn children(&self) -> &[DocumentSymbol] {
+        &self.children
+    }
+
+    pub(crate) fn nest(mut symbols: Vec<DocumentSymbol>) -> Vec<DocumentSymbol> {
+        symbols.sort_by(|a, b| {
+            a.range
+                .start
+                .cmp(&b.range.start)
+                .then(b.range.end.cmp(&a.range.end))
+        });
+        Self::nest_within(&mut symbols.into_iter().peekable(), None)
+    }
+
+    fn nest_within(
+        symbols: &mut std::iter::Peekable<impl Iterator<Item = DocumentSymbol>>,
+        parent: Option<Range>,
+    ) -> Vec<DocumentSymbol> {
+        let mut nested = Vec::new();
+        while let Some(next) = symbols.peek() {
+            if parent.is_some_and(|parent| !parent.contains(&next.range)) {
+                break;
+            }
+            let mut symbol = symbols.next().expect("peeked");
+            symbol.children = Self::nest_within(symbols, Some(symbol.range));
+            nested.push(symbol);
+        }
+        nested
+    }
+}
@@ -0,0 +1,270 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default)]
+pub struct SourceGroup {
+    name: String,
+    group_type: String,
+    enabled: bool,
+    source_paths: Vec<PathBuf>,
+    source_extensions: Vec<String>,
+    exclude_filters: Vec<String>,
+}
+
+impl SourceGroup {
+    pub const C: &'static str = "C Source Group";
+    pub const CXX: &'static str = "C++ Source Group";
+    pub const CXX_CDB: &'static str = "C/C++ from Compilation Database";
+    pub const JAVA: &'static str = "Java Source Group";
+    pub const PYTHON: &'static str = "Python Source Group";
+    pub const CUSTOM: &'static str = "Custom Source Group";
+
+    pub fn new(name: impl Into<String>, group_type: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            group_type: group_type.into(),
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn group_type(&self) -> &str {
+        &self.group_type
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.set_enabled(enabled);
+        self
+    }
+
+    pub fn source_paths(&self) -> &[PathBuf] {
+        &self.source_paths
+    }
+
+    pub fn add_source_path(&mut self, path: impl AsRef<Path>) {
+        self.source_paths.push(path.as_ref().to_path_buf());
+    }
+
+    pub fn source_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.add_source_path(path);
+        self
+    }
+
+    pub fn source_extensions(&self) -> &[String] {
+        &self.source_extensions
+    }
+
+    pub fn add_source_extension(&mut self, extension: impl Into<String>) {
+        self.source_extensions.push(extension.into());
+    }
+
+    pub fn source_extension(mut self, extension: impl Into<String>) -> Self {
+        self.add_source_extension(extension);
+        self
+    }
+
+    pub fn exclude_filters(&self) -> &[String] {
+        &self.exclude_filters
+    }
+
+    pub fn add_exclude_filter(&mut self, filter: impl Into<String>) {
+        self.exclude_filters.push(filter.into());
+    }
+
+    pub fn exclude_filter(mut self, filter: impl Into<String>) -> Self {
+        self.add_exclude_filter(filter);
+        self
+    }
+
+    fn write_xml(&self, index: usize, xml: &mut XmlWriter) {
+        let tag = format!("source_group_{index}");
+
+        xml.open(&tag);
+        xml.leaf("name", &self.name);
+        xml.leaf("status", if self.enabled { "enabled" } else { "disabled" });
+        xml.leaf("type", &self.group_type);
+
+        if !self.source_extensions.is_empty() {
+            xml.open("source_extensions");
+            for ext in &self.source_extensions {
+                xml.leaf("source_extension", ext);
+            }
+            xml.close("source_extensions");
+        }
+
+        if !self.source_paths.is_empty() {
+            xml.open("source_paths");
+            for path in &self.source_paths {
+                xml.leaf("source_path", &path.to_string_lossy());
+            }
+            xml.close("source_paths");
+        }
+
+        if !self.exclude_filters.is_empty() {
+            xml.open("exclude_filters");
+            for filter in &self.exclude_filters {
+                xml.leaf("exclude_filter", filter);
+            }
+            xml.close("exclude_filters");
+        }
+
+        xml.close(&tag);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProjectSettings {
+    version: u32,
+    name: Option<String>,
+    description: Option<String>,
+    source_groups: Vec<SourceGroup>,
+}
+
+impl ProjectSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.set_version(version);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.set_name(name);
+        self
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn set_description(&mut self, description: impl Into<String>) {
+        self.description = Some(description.into());
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.set_description(description);
+        self
+    }
+
+    pub fn source_groups(&self) -> &[SourceGroup] {
+        &self.source_groups
+    }
+
+    pub fn add_source_group(&mut self, group: SourceGroup) {
+        self.source_groups.push(group);
+    }
+
+    pub fn source_group(mut self, group: SourceGroup) -> Self {
+        self.add_source_group(group);
+        self
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut xml = XmlWriter::new();
+
+        xml.open("config");
+
+        if let Some(name) = &self.name {
+            xml.leaf("name", name);
+        }
+
+        if let Some(description) = &self.description {
+            xml.leaf("description", description);
+        }
+
+        if !self.source_groups.is_empty() {
+            xml.open("source_groups");
+            for (i, group) in self.source_groups.iter().enumerate() {
+                group.write_xml(i, &mut xml);
+            }
+            xml.close("source_groups");
+        }
+
+        xml.leaf("version", &self.version.to_string());
+        xml.close("config");
+
+        xml.finish()
+    }
+}
+
+struct XmlWriter {
+    out: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    fn new() -> Self {
+        Self {
+            out: String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#),
+            depth: 0,
+        }
+    }
+
+    fn indent(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.depth {
+            self.out.push_str("    ");
+        }
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.indent();
+        self.out.push_str(&format!("<{tag}>"));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str(&format!("</{tag}>"));
+    }
+
+    fn leaf(&mut self, tag: &str, text: &str) {
+        self.indent();
+        self.out.push_str(&format!("<{tag}>"));
+        for c in text.chars() {
+            match c {
+                '&' => self.out.push_str("&amp;"),
+                '<' => self.out.push_str("&lt;"),
+                '>' => self.out.push_str("&gt;"),
+                '"' => self.out.push_str("&quot;"),
+                '\'' => self.out.push_str("&apos;"),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push_str(&format!("</{tag}>"));
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
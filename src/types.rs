@@ -1,4 +1,7 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use derive_builder::Builder;
@@ -6,6 +9,35 @@ use num_enum::TryFromPrimitive;
 
 use crate::api::SourcetrailError;
 
+macro_rules! kind_names {
+    ($kind:ident { $($variant:ident => $name:literal),* $(,)? }) => {
+        impl $kind {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+
+        impl fmt::Display for $kind {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl FromStr for $kind {
+            type Err = SourcetrailError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($name => Ok(Self::$variant),)*
+                    _ => Err(SourcetrailError::UnknownKind(s.to_owned())),
+                }
+            }
+        }
+    };
+}
+
 #[derive(Debug)]
 pub struct Meta {
     id: i64,
@@ -56,11 +88,19 @@ impl Element {
     }
 }
 
-#[derive(Debug, Copy, Clone, TryFromPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, TryFromPrimitive)]
 #[repr(i32)]
 pub enum ElementComponentType {
     None = 0,
     IsAmbiguous = 1,
+    /// Marks a node as deprecated. Not a Sourcetrail kind; the value is kept
+    /// well above Sourcetrail's own kinds so that new upstream kinds cannot
+    /// collide with it. Sourcetrail ignores it.
+    Deprecated = 1000,
+    /// Free-text label attached to an edge (e.g. "argument", "return type").
+    /// Not a Sourcetrail kind either and numbered alongside `Deprecated` for
+    /// the same reason; Sourcetrail ignores it.
+    Label = 1001,
 }
 
 #[derive(Debug)]
@@ -109,7 +149,7 @@ impl ElementComponent {
     }
 }
 
-#[derive(Debug, Copy, Clone, TryFromPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, TryFromPrimitive)]
 #[repr(i32)]
 pub enum EdgeType {
     Undefined = 0,
@@ -128,7 +168,24 @@ pub enum EdgeType {
     AnnotationUsage = 1 << 12,
 }
 
-#[derive(Debug)]
+kind_names!(EdgeType {
+    Undefined => "undefined",
+    Member => "member",
+    TypeUsage => "type_usage",
+    Usage => "usage",
+    Call => "call",
+    Inheritance => "inheritance",
+    Override => "override",
+    TypeArgument => "type_argument",
+    TemplateSpecialization => "template_specialization",
+    Include => "include",
+    Import => "import",
+    BundledEdges => "bundled_edges",
+    MacroUsage => "macro_usage",
+    AnnotationUsage => "annotation_usage",
+});
+
+#[derive(Debug, Clone)]
 pub struct Edge {
     id: i64,
     type_: EdgeType,
@@ -169,7 +226,7 @@ impl Edge {
     }
 }
 
-#[derive(Debug, Copy, Clone, TryFromPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, TryFromPrimitive)]
 #[repr(i32)]
 pub enum NodeType {
     NodeSymbol = 1 << 0,
@@ -195,7 +252,74 @@ pub enum NodeType {
     NodeUnion = 1 << 20,
 }
 
-#[derive(Debug)]
+kind_names!(NodeType {
+    NodeSymbol => "symbol",
+    NodeType => "type",
+    NodeBuiltinType => "builtin_type",
+    NodeModule => "module",
+    NodeNamespace => "namespace",
+    NodePackage => "package",
+    NodeStruct => "struct",
+    NodeClass => "class",
+    NodeInterface => "interface",
+    NodeAnnotation => "annotation",
+    NodeGlobalVariable => "global_variable",
+    NodeField => "field",
+    NodeFunction => "function",
+    NodeMethod => "method",
+    NodeEnum => "enum",
+    NodeEnumConstant => "enum_constant",
+    NodeTypedef => "typedef",
+    NodeTypeParameter => "type_parameter",
+    NodeFile => "file",
+    NodeMacro => "macro",
+    NodeUnion => "union",
+});
+
+impl NodeType {
+    /// Whether a node of this type is a sensible parent for a `child` node.
+    /// Untyped scaffolding (`NodeSymbol`, `NodeType`) may contain anything.
+    pub fn can_contain(self, child: NodeType) -> bool {
+        match self {
+            Self::NodeSymbol | Self::NodeType => true,
+            Self::NodeModule | Self::NodeNamespace | Self::NodePackage => !matches!(
+                child,
+                Self::NodeFile
+                    | Self::NodeField
+                    | Self::NodeMethod
+                    | Self::NodeEnumConstant
+                    | Self::NodeTypeParameter
+            ),
+            Self::NodeStruct
+            | Self::NodeClass
+            | Self::NodeInterface
+            | Self::NodeAnnotation
+            | Self::NodeUnion => !matches!(
+                child,
+                Self::NodeFile
+                    | Self::NodeModule
+                    | Self::NodeNamespace
+                    | Self::NodePackage
+                    | Self::NodeEnumConstant
+            ),
+            Self::NodeEnum => matches!(
+                child,
+                Self::NodeEnumConstant | Self::NodeField | Self::NodeMethod
+            ),
+            Self::NodeFunction | Self::NodeMethod => matches!(child, Self::NodeTypeParameter),
+            Self::NodeBuiltinType
+            | Self::NodeGlobalVariable
+            | Self::NodeField
+            | Self::NodeEnumConstant
+            | Self::NodeTypedef
+            | Self::NodeTypeParameter
+            | Self::NodeFile
+            | Self::NodeMacro => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Node {
     id: i64,
     type_: NodeType,
@@ -280,6 +404,7 @@ impl Symbol {
 pub struct File {
     id: i64,
     path: PathBuf,
+    #[builder(default)]
     language: String,
     modification_time: DateTime<Utc>,
     indexed: bool,
@@ -444,9 +569,23 @@ pub enum SourceLocationType {
     Unsolved = 9,
 }
 
+kind_names!(SourceLocationType {
+    Token => "token",
+    Scope => "scope",
+    Qualifier => "qualifier",
+    LocalSymbol => "local_symbol",
+    Signature => "signature",
+    AtomicRange => "atomic_range",
+    IndexerError => "indexer_error",
+    FulltextSearch => "fulltext_search",
+    ScreenSearch => "screen_search",
+    Unsolved => "unsolved",
+});
+
 #[derive(Debug, Builder)]
 #[builder(setter(into), build_fn(validate = "Self::validate"))]
 pub struct SourceLocation {
+    #[builder(default)]
     id: i64,
     file_node_id: i64,
     start_line: i32,
@@ -520,7 +659,23 @@ impl SourceLocation {
         })
     }
 
-    pub fn builder(self) -> SourceLocationBuilder {
+    /// ```
+    /// use sourcetrail::types::{SourceLocation, SourceLocationType};
+    ///
+    /// let location = SourceLocation::builder()
+    ///     .file_node_id(1)
+    ///     .start_line(3)
+    ///     .start_column(5)
+    ///     .end_line(3)
+    ///     .end_column(9)
+    ///     .location_type(SourceLocationType::Token)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(location.start_line(), 3);
+    /// assert_eq!(location.location_type(), SourceLocationType::Token);
+    /// ```
+    pub fn builder() -> SourceLocationBuilder {
         SourceLocationBuilder::default()
     }
 
@@ -600,6 +755,12 @@ pub struct ComponentAccess {
     type_: ComponentAccessType,
 }
 
+impl AsRef<ComponentAccess> for ComponentAccess {
+    fn as_ref(&self) -> &ComponentAccess {
+        self
+    }
+}
+
 impl ComponentAccess {
     pub fn new(node_id: i64, type_: ComponentAccessType) -> Self {
         ComponentAccess { node_id, type_ }
@@ -612,6 +773,10 @@ impl ComponentAccess {
     pub fn access_type(&self) -> ComponentAccessType {
         self.type_
     }
+
+    pub fn set_access_type(&mut self, type_: ComponentAccessType) {
+        self.type_ = type_;
+    }
 }
 
 #[derive(Debug, Default, Builder)]
@@ -677,7 +842,7 @@ impl Error {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NameHierarchy {
     delimiter: String,
     elements: Vec<NameElement>,
@@ -711,6 +876,68 @@ impl NameHierarchy {
         })
     }
 
+    /// Builds a hierarchy with one element per part, each without prefix or
+    /// postfix.
+    pub fn from_parts(
+        delimiter: impl Into<String>,
+        parts: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Self, SourcetrailError> {
+        Self::new(
+            delimiter,
+            parts
+                .into_iter()
+                .map(|part| NameElement::builder().name(part.into()).build()),
+        )
+    }
+
+    pub fn cxx(
+        parts: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Self, SourcetrailError> {
+        Self::from_parts(Self::NAME_DELIMITER_CXX, parts)
+    }
+
+    pub fn java(
+        parts: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Self, SourcetrailError> {
+        Self::from_parts(Self::NAME_DELIMITER_JAVA, parts)
+    }
+
+    /// Builds a hierarchy from a plain C++ qualified name, e.g.
+    /// `std::vector::iterator`.
+    pub fn from_cxx_qualified_name(name: &str) -> Result<Self, SourcetrailError> {
+        Self::from_qualified_name(name, Self::NAME_DELIMITER_CXX)
+    }
+
+    /// Builds a hierarchy from a plain Java qualified name, e.g.
+    /// `com.example.MyClass.method`.
+    pub fn from_java_qualified_name(name: &str) -> Result<Self, SourcetrailError> {
+        Self::from_qualified_name(name, Self::NAME_DELIMITER_JAVA)
+    }
+
+    /// Builds a hierarchy by splitting `name` on `delimiter`; a single
+    /// leading or trailing delimiter is ignored.
+    pub fn from_qualified_name(name: &str, delimiter: &str) -> Result<Self, SourcetrailError> {
+        let trimmed = name.strip_prefix(delimiter).unwrap_or(name);
+        let trimmed = trimmed.strip_suffix(delimiter).unwrap_or(trimmed);
+
+        if trimmed.is_empty() {
+            return Err(SourcetrailError::EmptyNameHierarchy);
+        }
+
+        let elements = trimmed
+            .split(delimiter)
+            .map(|part| {
+                if part.is_empty() {
+                    Err(SourcetrailError::InvalidQualifiedName(name.to_owned()))
+                } else {
+                    Ok(NameElement::builder().name(part).build())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(delimiter, elements)
+    }
+
     pub fn delimiter(&self) -> &str {
         &self.delimiter
     }
@@ -740,15 +967,19 @@ impl NameHierarchy {
             .map(|e| {
                 format!(
                     "{}\ts{}\tp{}",
-                    e.name.as_deref().unwrap_or_default(),
-                    e.prefix.as_deref().unwrap_or_default(),
-                    e.postfix.as_deref().unwrap_or_default()
+                    escape_name_part(e.name.as_deref().unwrap_or_default()),
+                    escape_name_part(e.prefix.as_deref().unwrap_or_default()),
+                    escape_name_part(e.postfix.as_deref().unwrap_or_default())
                 )
             })
             .collect::<Vec<String>>()
             .join("\tn");
 
-        Ok(format!("{}\tm{}", self.delimiter, serialized))
+        Ok(format!(
+            "{}\tm{}",
+            escape_name_part(&self.delimiter),
+            serialized
+        ))
     }
 
     pub fn serialize_name(&self) -> Result<String, SourcetrailError> {
@@ -758,44 +989,99 @@ impl NameHierarchy {
     pub fn deserialize_name(
         serialized_name: impl AsRef<str>,
     ) -> Result<NameHierarchy, SourcetrailError> {
-        let serialized_name = serialized_name.as_ref();
-        let idx = serialized_name
-            .find("\tm")
-            .ok_or(SourcetrailError::Deserialize)?;
+        let mut parts = split_name_parts(serialized_name.as_ref()).into_iter();
+
+        let delimiter = match parts.next() {
+            Some((delimiter, Some('m'))) => delimiter,
+            _ => return Err(SourcetrailError::Deserialize),
+        };
 
-        let delimiter = &serialized_name[0..idx];
+        // each element is `name[\tsprefix[\tppostfix]]`; missing prefix/postfix
+        // segments are read as empty
         let mut elements = Vec::new();
+        let mut fields = [String::new(), String::new(), String::new()];
+        let mut field = 0;
+
+        for (text, marker) in parts {
+            fields[field] = text;
+            match marker {
+                Some('s') if field == 0 => field = 1,
+                Some('p') if field < 2 => field = 2,
+                Some('n') | None => {
+                    let [name, prefix, postfix] = std::mem::take(&mut fields);
+                    elements.push(
+                        NameElementBuilder::new()
+                            .prefix(prefix)
+                            .name(name)
+                            .postfix(postfix)
+                            .build(),
+                    );
+                    field = 0;
+                }
+                _ => return Err(SourcetrailError::Deserialize),
+            }
+        }
+
+        NameHierarchy::new(delimiter, elements)
+    }
 
-        let parts = serialized_name[idx + 2..].split("\tn").collect::<Vec<_>>();
+    /// The qualified name for display, e.g. `std::vector<T>::size`.
+    pub fn to_display_string(&self) -> String {
+        self.elements
+            .iter()
+            .map(NameElement::to_string)
+            .collect::<Vec<_>>()
+            .join(&self.delimiter)
+    }
+}
 
-        for part in parts {
-            let segments = part.split("\ts").collect::<Vec<_>>();
-            if segments.len() < 2 {
-                return Err(SourcetrailError::Deserialize);
-            }
+impl fmt::Display for NameHierarchy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_display_string())
+    }
+}
 
-            let name = segments[0].to_owned();
-            let signature_postfix = segments[1].split("\tp").collect::<Vec<_>>();
-            if signature_postfix.len() < 2 {
-                return Err(SourcetrailError::Deserialize);
-            }
+// Names are serialized with `\t` followed by `m`, `n`, `s` or `p` as markers;
+// literal tabs within names are doubled so they cannot be read as markers.
+fn escape_name_part(part: &str) -> Cow<'_, str> {
+    if part.contains('\t') {
+        Cow::Owned(part.replace('\t', "\t\t"))
+    } else {
+        Cow::Borrowed(part)
+    }
+}
 
-            let prefix = signature_postfix[0].to_owned();
-            let postfix = signature_postfix[1].to_owned();
-            elements.push(
-                NameElementBuilder::new()
-                    .prefix(prefix)
-                    .name(name)
-                    .postfix(postfix)
-                    .build(),
-            );
+// Splits a serialized name into unescaped parts, each paired with the marker
+// that ends it (`None` for the last part).
+fn split_name_parts(serialized_name: &str) -> Vec<(String, Option<char>)> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut chars = serialized_name.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\t' {
+            current.push(c);
+            continue;
         }
 
-        NameHierarchy::new(delimiter, elements)
+        match chars.peek().copied() {
+            Some('\t') => {
+                chars.next();
+                current.push('\t');
+            }
+            Some(marker @ ('m' | 'n' | 's' | 'p')) => {
+                chars.next();
+                parts.push((std::mem::take(&mut current), Some(marker)));
+            }
+            _ => current.push(c),
+        }
     }
+
+    parts.push((current, None));
+    parts
 }
 
-#[derive(Debug, Builder, Default)]
+#[derive(Debug, Clone, Builder, Default)]
 #[builder(build_fn(skip), pattern = "owned")]
 pub struct NameElement {
     #[builder(setter(into, strip_option))]
@@ -867,3 +1153,15 @@ impl NameElement {
         self.postfix = Some(postfix.into());
     }
 }
+
+impl fmt::Display for NameElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in [&self.prefix, &self.name, &self.postfix]
+            .into_iter()
+            .flatten()
+        {
+            f.write_str(part)?;
+        }
+        Ok(())
+    }
+}